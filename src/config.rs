@@ -0,0 +1,55 @@
+use std::io;
+use std::path::Path;
+
+use serde::Deserialize;
+
+/// Extra search locations shared by a fleet, read from a JSON file such as
+/// `where-is-it.json`:
+///
+/// ```json
+/// { "jvm_paths": ["/opt/jdks"], "python_paths": ["/opt/python/bin"], "disabled_providers": ["conda"] }
+/// ```
+///
+/// Every key is optional.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Extra directories containing JVMs, see `java::MatchOptions::paths`
+    pub jvm_paths: Vec<String>,
+    /// Extra directories containing Python interpreters, see `python::MatchOptions::extra_paths`
+    pub python_paths: Vec<String>,
+    /// Python providers to leave out, in addition to `WHERE_IS_IT_DISABLE_PROVIDERS`
+    pub disabled_providers: Vec<String>,
+}
+
+impl Config {
+    /// Read the config file at `path`. A missing file gives the default (empty) config,
+    /// while an unreadable or malformed one is an error.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Self::parse(&contents),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn parse(contents: &str) -> io::Result<Self> {
+        serde_json::from_str(contents).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let config = Config::parse(r#"{ "jvm_paths": ["/opt/jdks"], "disabled_providers": ["conda"] }"#).unwrap();
+        assert_eq!(config.jvm_paths, vec!["/opt/jdks".to_string()]);
+        assert!(config.python_paths.is_empty());
+        assert_eq!(config.disabled_providers, vec!["conda".to_string()]);
+
+        assert!(Config::parse("{ \"jvm_paths\": 1 }").is_err());
+        assert_eq!(Config::load(Path::new("/does/not/exist.json")).unwrap(), Config::default());
+    }
+}
@@ -1,11 +1,19 @@
 // Heavily adapted from https://github.com/dameikle/javalocate
 
+// Discovery is stubbed out on wasm, leaving most of the helpers unused there
+#![cfg_attr(target_arch = "wasm32", allow(dead_code))]
+
 use std::cmp::Ordering;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
 use std::fs;
+use std::sync::Mutex;
+use std::io;
+#[cfg(not(target_arch = "wasm32"))]
 use std::fs::File;
-use std::hash::Hash;
-use std::io::{self, BufReader};
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::{BufReader, Read};
+#[cfg(not(target_arch = "wasm32"))]
 use java_properties::read;
 
 #[cfg(target_os = "macos")]
@@ -20,35 +28,279 @@ extern crate winreg;
 use winreg::RegKey;
 #[cfg(target_os = "windows")]
 use winreg::enums::HKEY_LOCAL_MACHINE;
-#[cfg(target_os = "windows")]
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-#[cfg(target_os = "linux")]
-use std::collections::HashMap;
 
 #[cfg(feature = "node-compile")]
 use napi_derive::napi;
 
 /// Command line utility to find JVM versions on macOS, Linux and Windows
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct MatchOptions {
     /// JVM Name to filter on
     pub name: Option<String>,
 
+    /// Regular expression the JVM name must match (e.g. `Temurin.*17`), used instead
+    /// of `name` when set. An invalid expression matches no JVM
+    pub name_regex: Option<String>,
+
+    /// Vendor to filter on, matched case-insensitively against part of the
+    /// JVM's vendor or name (e.g. temurin, zulu, corretto)
+    pub vendor: Option<String>,
+
     /// Architecture to filter on (e.g. x86_64, aarch64, amd64)
     pub arch: Option<String>,
 
-    /// Version to filter on (e.g. 1.8, 11, 17, etc)
-    pub version: Option<String>
+    /// Pointer width to filter on (32 or 64), see `Jvm::bits`. JVMs whose width
+    /// isn't known are dropped
+    pub bits: Option<u8>,
+
+    /// Version to filter on (e.g. 1.8, 11, 17, etc), see `Jvm::satisfies`. A feature
+    /// release minimum such as `8+` also matches legacy versions like 1.8.0_292
+    pub version: Option<String>,
+
+    /// Report JVMs whose paths resolve to the same install only once
+    pub dedup_by_realpath: Option<bool>,
+
+    /// Drop early-access (pre-GA) builds
+    pub exclude_ea: Option<bool>,
+
+    /// Only keep JVMs bundling JavaFX
+    pub javafx_only: Option<bool>,
+
+    /// Only keep JVMs linked against this C library (`glibc` or `musl`). JVMs whose
+    /// C library isn't known are kept
+    pub libc: Option<String>,
+
+    /// Only keep long-term support releases (see `LTS_MAJOR_VERSIONS`), dropping
+    /// every other major version
+    pub lts_only: Option<bool>,
+
+    /// Release file properties holding the version, in order of preference - e.g. to
+    /// also accept a vendor specific SEMERU_VERSION. `RELEASE_VERSION_KEYS` when not set
+    pub version_keys: Option<Vec<String>>,
+
+    /// Environment variables pointing at a JDK home to report (marked with `Jvm::from_env`).
+    /// `JDK_HOME_ENV_VARS` when not set, and an empty list skips the environment
+    pub env_vars: Option<Vec<String>>,
+
+    /// Only return the highest matching version, e.g. the newest 17.x for version 17,
+    /// even when `sort` is off. Every other filter still applies
+    pub highest_only: Option<bool>,
+
+    /// Keep JVMs whose version is missing or unparsable (e.g. a custom build without
+    /// `JAVA_VERSION` in its release file), sorted after every other JVM. Dropped by default
+    pub include_unknown_version: Option<bool>,
+
+    /// Extra directories containing JVMs to scan. With the `glob` feature these
+    /// may be patterns (e.g. /opt/*/jvm) matching such directories
+    pub paths: Vec<String>,
+
+    /// Also report the symlinks to JDK homes in `paths`, e.g. a `~/jdks` directory
+    /// linking the preferred JDKs. JVMs are reported once per canonical home. Only
+    /// changes the Linux scan, which otherwise skips links (as it still does in the
+    /// system directories)
+    pub follow_symlinks: Option<bool>,
+
+    /// Vendors to prefer among JVMs of the same version and architecture, most preferred
+    /// first (e.g. temurin, zulu). Matched like `vendor`, and unlisted vendors come last
+    pub vendor_priority: Vec<String>,
+
+    /// `Some(false)` to keep the JVMs in discovery order (environment variables last)
+    /// instead of newest first, e.g. to inspect what the scans found
+    pub sort: Option<bool>,
+
+    /// Drop JVMs whose canonical home is inside one of these directories. Whole
+    /// path components are compared, so /opt/old doesn't exclude /opt/older
+    pub exclude_paths: Vec<String>
+}
+
+impl MatchOptions {
+    /// Parse a `[vendor@]version` spec such as `17`, `11+`, `8u292` or `temurin@17`.
+    /// A spec whose version part isn't a valid version is used as a name filter instead.
+    pub fn parse(spec: &str) -> Self {
+        let (vendor, version) = match spec.split_once('@') {
+            Some((vendor, version)) => (Some(vendor), version),
+            None => (None, spec)
+        };
+
+        let is_version = !version.is_empty() && version.chars().next().unwrap().is_ascii_digit()
+            && version.chars().all(|c| c.is_ascii_digit() || ".+_u".contains(c));
+        if !is_version {
+            return MatchOptions {
+                name: Some(spec.to_string()),
+                ..Default::default()
+            };
+        }
+
+        MatchOptions {
+            vendor: vendor.filter(|v| !v.is_empty()).map(|v| v.to_string()),
+            version: Some(version.to_string()),
+            ..Default::default()
+        }
+    }
 }
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "node-compile", napi)]
 pub struct Jvm {
+    /// The comparable core version, without any build suffix - e.g. 1.8.0_382
     pub version: String,
+    /// The JAVA_VERSION from the release file as-is - e.g. 1.8.0_382-b05
+    pub full_version: String,
     pub name: String,
+    /// The IMPLEMENTOR from the release file, empty when unknown
+    pub vendor: String,
     pub architecture: String,
-    pub path: String
+    /// The pointer width (32 or 64) of the architecture, read from the header of
+    /// `bin/java` when the release file doesn't name the architecture
+    pub bits: Option<u8>,
+    pub path: String,
+    /// The JAVA_VERSION_DATE (or BUILD_DATE) from the release file
+    pub release_date: Option<String>,
+    /// Whether this is an early-access (pre-GA) build
+    pub early_access: bool,
+    /// Whether this JVM bundles JavaFX (e.g. Liberica Full or Zulu FX builds)
+    pub has_javafx: bool,
+    /// The C library `bin/java` is linked against on Linux (`glibc` or `musl`), if known
+    pub libc: Option<String>,
+    /// Whether this is the JDK selected by jenv for the current directory
+    pub is_default: bool,
+    /// The environment variable pointing at this JDK's home (e.g. JAVA_HOME), if any
+    pub from_env: Option<String>,
+    /// Whether this is a runtime bundled inside an application, see `inspect_tree`
+    pub embedded: bool
+}
+
+lazy_static::lazy_static! {
+    /// The release files read by `Jvm::release_property`, by JVM home
+    static ref RELEASE_PROPERTIES: Mutex<HashMap<PathBuf, HashMap<String, String>>> = Mutex::new(HashMap::new());
+}
+
+impl Jvm {
+    /// The path to the `java` executable of this JVM
+    pub fn java_executable(&self) -> PathBuf {
+        let java = if cfg!(windows) { "java.exe" } else { "java" };
+        Path::new(&self.path).join("bin").join(java)
+    }
+
+    /// The feature release number, with old style versions normalised - e.g. 1.8.0_292 -> 8
+    pub fn major_version(&self) -> Option<u32> {
+        parse_major_version(&self.version)
+    }
+
+    /// The highest class file version this JVM supports, see `class_file_version`.
+    /// Falls back to the JAVA_VERSION of the release file when the version is unknown.
+    pub fn class_version(&self) -> Option<u32> {
+        let major = self
+            .major_version()
+            .or_else(|| parse_major_version(&self.release_property("JAVA_VERSION")?))?;
+        Some(class_file_version(major))
+    }
+
+    /// Whether this JVM matches a version spec, as used by `MatchOptions::version`:
+    /// an exact version (`17`, `1.8.0_292`, `8u292`), a minimum (`11+`) or a comma
+    /// separated list of these matching if any of them does. An empty spec matches all.
+    pub fn satisfies(&self, version_spec: &str) -> bool {
+        let versions: Vec<&str> = version_spec
+            .split(',')
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+            .collect();
+        versions.is_empty() || versions.iter().any(|v| matches_version(v, self))
+    }
+
+    /// Any property of the release file (e.g. `MODULES` or `OS_NAME`) without its
+    /// quotes. The file of each home is read once and kept for the rest of the
+    /// process; `None` when it or the key is missing.
+    pub fn release_property(&self, key: &str) -> Option<String> {
+        let mut cache = RELEASE_PROPERTIES.lock().unwrap_or_else(|err| err.into_inner());
+        cache
+            .entry(PathBuf::from(&self.path))
+            .or_insert_with(|| read_release_properties(Path::new(&self.path)))
+            .get(key)
+            .map(|value| value.replace("\"", ""))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Jvm {
+    /// Builds the JVM at `home` from the properties of its release file, named after
+    /// the home directory. Scanners rename it when they know better.
+    fn from_release(home: &Path, properties: &HashMap<String, String>, version_keys: &[String]) -> Option<Jvm> {
+        let full_version = release_version(properties, version_keys);
+        let architecture = normalize_arch(&properties.get("OS_ARCH").unwrap_or(&"".to_string()).replace("\"", ""));
+        Some(Jvm {
+            vendor: properties.get("IMPLEMENTOR").unwrap_or(&"".to_string()).replace("\"", ""),
+            release_date: get_release_date(properties),
+            early_access: is_early_access(properties),
+            ..Jvm::from_home(home, full_version, architecture)?
+        })
+    }
+
+    /// Builds the JVM at `home` from its version and architecture, the other details
+    /// being read from the home itself. `None` when the path isn't valid UTF-8.
+    fn from_home(home: &Path, full_version: String, architecture: String) -> Option<Jvm> {
+        let header = launcher_header(home);
+        Some(Jvm {
+            version: core_version(&full_version),
+            full_version,
+            bits: jvm_bits(&architecture, &header),
+            architecture,
+            name: home.file_name().map(|f| f.to_string_lossy().to_string()).unwrap_or_default(),
+            vendor: String::new(),
+            release_date: None,
+            early_access: false,
+            has_javafx: has_javafx(home),
+            libc: detect_libc(&header),
+            path: home.to_str()?.to_string(),
+            is_default: false,
+            from_env: None,
+            embedded: false
+        })
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_release_properties(home: &Path) -> HashMap<String, String> {
+    File::open(home.join("release"))
+        .ok()
+        .and_then(|release_file| read(BufReader::new(release_file)).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_release_properties(_home: &Path) -> HashMap<String, String> {
+    HashMap::new()
+}
+
+#[napi]
+#[cfg(feature = "node-compile")]
+impl Jvm {
+    #[napi(getter)]
+    pub fn major(&self) -> Option<u32> {
+        self.major_version()
+    }
+
+    #[napi(getter, js_name = "classVersion")]
+    pub fn js_class_version(&self) -> Option<u32> {
+        self.class_version()
+    }
+}
+
+// The feature release number of a version string - e.g. 1.8.0_292 -> 8, 17.0.8 -> 17
+fn parse_major_version(version: &str) -> Option<u32> {
+    let version = version.strip_prefix("1.").unwrap_or(version);
+    let major: String = version.chars().take_while(|c| c.is_ascii_digit()).collect();
+    major.parse().ok()
+}
+
+/// The class file major version introduced by a feature release, which is the
+/// release plus 44 since Java 1.1 - e.g. 8 -> 52, 11 -> 55, 17 -> 61, 21 -> 65.
+/// Java 1.0 shares version 45 with Java 1.1.
+pub fn class_file_version(major: u32) -> u32 {
+    major.max(1) + 44
 }
 
 #[derive(Clone)]
@@ -57,70 +309,438 @@ struct OperatingSystem {
     architecture: String
 }
 
-#[derive(Default)]
 struct Config {
-    paths: Vec<String>
+    paths: Vec<String>,
+    env_vars: Vec<String>,
+    version_keys: Vec<String>,
+    sort: bool,
+    vendor_priority: Vec<String>,
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    follow_symlinks: bool
+}
+
+impl Config {
+    fn from_options(args: &MatchOptions) -> Self {
+        let env_vars = match &args.env_vars {
+            Some(env_vars) => env_vars.clone(),
+            None => JDK_HOME_ENV_VARS.iter().map(|v| v.to_string()).collect()
+        };
+        let version_keys = match &args.version_keys {
+            Some(version_keys) => version_keys.clone(),
+            None => RELEASE_VERSION_KEYS.iter().map(|k| k.to_string()).collect()
+        };
+        Config {
+            paths: args.paths.clone(),
+            env_vars,
+            version_keys,
+            sort: args.sort.unwrap_or(true),
+            vendor_priority: lowercase_vendors(&args.vendor_priority),
+            follow_symlinks: args.follow_symlinks.unwrap_or(false)
+        }
+    }
 }
 
+/// Release file properties holding the version, in order of preference, used unless
+/// `MatchOptions::version_keys` is set
+pub static RELEASE_VERSION_KEYS: [&str; 2] = ["JAVA_VERSION", "JAVA_RUNTIME_VERSION"];
+
+/// The major versions of the long-term support releases
+pub const LTS_MAJOR_VERSIONS: &[u32] = &[8, 11, 17, 21, 25];
+
+/// Environment variables pointing at a JDK home, checked unless `MatchOptions::env_vars` is set
+pub static JDK_HOME_ENV_VARS: [&str; 3] = ["JAVA_HOME", "JDK_HOME", "JAVA_SDK"];
+
 pub fn run(args: MatchOptions) -> Vec<Jvm> {
-    let cfg: Config = Default::default();
+    try_run(args).unwrap_or_default()
+}
+
+/// Same as `run`, but reports why discovery failed (e.g. an unsupported OS)
+/// instead of returning no JVMs
+pub fn try_run(args: MatchOptions) -> io::Result<Vec<Jvm>> {
+    let cfg = Config::from_options(&args);
 
     // Fetch default java architecture based on kernel
     let operating_system = match get_operating_system() {
         Some(os) => os,
-        None => return vec![]
+        None => return Err(io::Error::new(io::ErrorKind::Unsupported, "unsupported operating system or architecture"))
     };
 
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    let jenv = get_jenv_selection();
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    let jenv = None;
+
     // Build and filter JVMs
-    let jvms: Vec<Jvm> = match collate_jvms(&operating_system, &cfg) {
-        Ok(j) => j.into_iter()
-                  .filter(|tmp| filter_arch(&args.arch, tmp))
-                  .filter(|tmp| filter_ver(&args.version, tmp))
-                  .filter(|tmp| filter_name(&args.name, tmp))
-                  .collect(),
-        Err(_) => vec![]
+    Ok(select_jvms(collate_jvms(&operating_system, &cfg)?, &args, &operating_system.architecture, jenv))
+}
+
+// Filter the sorted JVMs and apply the options shaping the results, `jenv` being
+// the jenv root and the version it selects
+fn select_jvms(jvms: Vec<Jvm>, args: &MatchOptions, default_arch: &str, jenv: Option<(PathBuf, String)>) -> Vec<Jvm> {
+    let name_regex = compile_name_regex(&args.name_regex);
+    let jvms: Vec<Jvm> = jvms
+        .into_iter()
+        .filter(|tmp| filter_jvm(args, &name_regex, tmp))
+        .collect();
+
+    let jvms = if args.dedup_by_realpath.unwrap_or(false) {
+        dedup_by_realpath(jvms)
+    } else {
+        jvms
+    };
+
+    // Sorted newest first, preferring the host architecture, unless kept in discovery order.
+    // Done before the jenv default is moved to the front, so the highest version still wins
+    let mut jvms = jvms;
+    if args.highest_only.unwrap_or(false) {
+        if args.sort.unwrap_or(true) {
+            jvms.truncate(1);
+        } else {
+            let vendor_priority = lowercase_vendors(&args.vendor_priority);
+            jvms = jvms
+                .into_iter()
+                .min_by(|a, b| compare_boosting_architecture(a, b, default_arch, &vendor_priority))
+                .into_iter()
+                .collect();
+        }
+    }
+
+    match jenv {
+        Some((jenv_root, version)) => mark_jenv_default(jvms, &jenv_root, &version, args.sort.unwrap_or(true)),
+        None => jvms
+    }
+}
+
+/// Same as `run`, with the extra JVM paths of the JSON config file at `path` merged
+/// in. A missing file is the same as an empty one.
+#[cfg(feature = "config")]
+pub fn run_with_config(path: &Path, args: MatchOptions) -> io::Result<Vec<Jvm>> {
+    let config = crate::config::Config::load(path)?;
+    let mut args = args;
+    args.paths.extend(config.jvm_paths);
+    Ok(run(args))
+}
+
+/// Refreshes the JVMs of a previous run (e.g. deserialized from a cache) without
+/// scanning: a cached JVM is kept as it is while its `java` executable is there and
+/// its release file still reports the cached version (read with `args.version_keys`),
+/// and dropped otherwise. Only with `rescan` does a fresh `run` with `args` add the
+/// JVMs found in homes that weren't cached, after the cached ones, newest first.
+pub fn reconcile(cached: Vec<Jvm>, args: MatchOptions, rescan: bool) -> Vec<Jvm> {
+    let version_keys = Config::from_options(&args).version_keys;
+    let cached = cached.into_iter().filter(|jvm| is_unchanged(jvm, &version_keys)).collect();
+    let scanned = if rescan { run(args) } else { vec![] };
+    reconcile_jvms(cached, scanned)
+}
+
+// Whether a cached JVM is still installed as it was. Homes recognised by their folder
+// name have no release file, so only their executable is checked
+fn is_unchanged(jvm: &Jvm, version_keys: &[String]) -> bool {
+    if !jvm.java_executable().is_file() {
+        return false;
+    }
+    let home = Path::new(&jvm.path);
+    !home.join("release").is_file() || release_version(&read_release_properties(home), version_keys) == jvm.full_version
+}
+
+fn reconcile_jvms(cached: Vec<Jvm>, scanned: Vec<Jvm>) -> Vec<Jvm> {
+    let mut jvms = cached;
+    let mut homes: HashSet<PathBuf> = jvms.iter().map(|jvm| real_path(&jvm.path)).collect();
+    for jvm in scanned {
+        if homes.insert(real_path(&jvm.path)) {
+            jvms.push(jvm);
+        }
+    }
+    jvms
+}
+
+/// Writes each matching JVM to `writer` as a JSON object on its own line (JSON
+/// Lines), flushing after each one, so a consumer can start before the scan
+/// finishes. JVMs are written in discovery order, see `iter`.
+#[cfg(feature = "serde")]
+pub fn write_jsonl<W: io::Write>(args: MatchOptions, writer: &mut W) -> io::Result<()> {
+    for jvm in iter(args) {
+        serde_json::to_writer(&mut *writer, &jvm)?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+/// Lazily yields the matching JVMs as their directories are parsed, so a consumer
+/// can take the first few without parsing every JDK. JVMs are yielded in discovery
+/// order: unlike `run`, they are not sorted, deduplicated by real path, nor marked
+/// with the jenv default, since all of that requires buffering every result.
+pub fn iter(args: MatchOptions) -> impl Iterator<Item = Jvm> {
+    let cfg = Config::from_options(&args);
+
+    // JVMs from the environment come first, and aren't repeated by the scans
+    let operating_system = get_operating_system();
+    let env = match operating_system {
+        Some(_) => env_jvms(&cfg.env_vars, &cfg.version_keys, |var| std::env::var_os(var)),
+        None => vec![]
+    };
+    let env_paths: Vec<PathBuf> = env.iter().map(|jvm| real_path(&jvm.path)).collect();
+    let scanned = operating_system
+        .map(|os| scan_jvms(&os, &cfg))
+        .into_iter()
+        .flatten()
+        .filter(move |jvm| !env_paths.contains(&real_path(&jvm.path)));
+
+    let name_regex = compile_name_regex(&args.name_regex);
+    let mut seen = HashSet::new();
+    env.into_iter()
+        .chain(scanned)
+        .filter(move |jvm| filter_jvm(&args, &name_regex, jvm) && seen.insert(jvm.clone()))
+}
+
+fn real_path(path: &str) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path))
+}
+
+/// JVMs from the JDK home environment variables that are set, as read by `lookup`,
+/// the first variable winning when several point at the same home
+fn env_jvms(env_vars: &[String], version_keys: &[String], lookup: impl Fn(&str) -> Option<OsString>) -> Vec<Jvm> {
+    let mut jvms: Vec<Jvm> = vec![];
+    for var in env_vars {
+        let home = match lookup(var) {
+            Some(home) if !home.is_empty() => PathBuf::from(home),
+            _ => continue
+        };
+        let real_home = fs::canonicalize(&home).unwrap_or_else(|_| home.clone());
+        if jvms.iter().any(|jvm| real_path(&jvm.path) == real_home) {
+            continue;
+        }
+        if let Some(mut jvm) = load_release_home(&home, version_keys) {
+            jvm.from_env = Some(var.clone());
+            jvms.push(jvm);
+        }
+    }
+    jvms
+}
+
+/// Builds a JVM from the release file of its home, named after the home directory
+#[cfg(not(target_arch = "wasm32"))]
+fn load_release_home(home: &Path, version_keys: &[String]) -> Option<Jvm> {
+    home.file_name()?;
+    let release_file = File::open(home.join("release")).ok()?;
+    let properties = read(BufReader::new(release_file)).ok()?;
+    Jvm::from_release(home, &properties, version_keys)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn load_release_home(_home: &Path, _version_keys: &[String]) -> Option<Jvm> {
+    None
+}
+
+/// Whether any JVM matches, without deduplicating or ranking the results
+pub fn any(args: MatchOptions) -> bool {
+    iter(args).next().is_some()
+}
+
+/// Builds the JVM at a known home from its `release` file, without scanning
+/// the usual locations. `None` when the home has no readable release file
+pub fn inspect(home: &str) -> Option<Jvm> {
+    let version_keys: Vec<String> = RELEASE_VERSION_KEYS.iter().map(|k| k.to_string()).collect();
+    load_release_home(Path::new(home), &version_keys)
+}
+
+/// `inspect` for several homes, skipping those without a readable release file.
+/// Sorted like the results of `run`, newest first.
+pub fn inspect_all(homes: &[String]) -> Vec<Jvm> {
+    let mut jvms: Vec<Jvm> = homes.iter().filter_map(|home| inspect(home)).collect();
+    let default_arch = get_operating_system().map(|os| os.architecture).unwrap_or_default();
+    jvms.sort_by(|a, b| compare_boosting_architecture(a, b, &default_arch, &[]));
+    jvms
+}
+
+/// How deep `inspect_tree` descends, enough for e.g. `App.app/Contents/runtime/Contents/Home`
+const INSPECT_TREE_DEPTH: usize = 6;
+
+/// The runtimes bundled anywhere under an application directory, e.g. `<App>/runtime`
+/// or `<App>.app/Contents/runtime/Contents/Home`, marked with `Jvm::embedded`. Unlike
+/// the `paths` scan this descends into subdirectories, stopping at each runtime found
+/// and after a few levels. Symlinked directories aren't followed.
+pub fn inspect_tree(root: &str) -> Vec<Jvm> {
+    let mut jvms = vec![];
+    inspect_dir(Path::new(root), INSPECT_TREE_DEPTH, &mut jvms);
+    jvms
+}
+
+fn inspect_dir(dir: &Path, depth: usize, jvms: &mut Vec<Jvm>) {
+    let java = if cfg!(windows) { "java.exe" } else { "java" };
+    if dir.join("release").is_file() && dir.join("bin").join(java).is_file() {
+        if let Some(mut jvm) = dir.to_str().and_then(inspect) {
+            jvm.embedded = true;
+            jvms.push(jvm);
+            return;
+        }
+    }
+    if depth == 0 {
+        return;
+    }
+    let mut subdirs: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().map(|t| t.is_dir()).unwrap_or(false))
+            .map(|entry| entry.path())
+            .collect(),
+        Err(_) => return
     };
+    subdirs.sort();
+    for subdir in subdirs {
+        inspect_dir(&subdir, depth - 1, jvms);
+    }
+}
+
+// `name_regex` is `args.name_regex` compiled by `compile_name_regex`
+fn filter_jvm(args: &MatchOptions, name_regex: &Option<Option<fancy_regex::Regex>>, jvm: &Jvm) -> bool {
+    filter_arch(&args.arch, jvm)
+        && filter_bits(&args.bits, jvm)
+        && filter_ver(&args.version, jvm)
+        && match name_regex {
+            Some(regex) => filter_name_regex(regex, jvm),
+            None => filter_name(&args.name, jvm)
+        }
+        && filter_vendor(&args.vendor, jvm)
+        && filter_ea(&args.exclude_ea, jvm)
+        && filter_javafx(&args.javafx_only, jvm)
+        && filter_lts(&args.lts_only, jvm)
+        && filter_libc(&args.libc, jvm)
+        && filter_exclude_paths(&args.exclude_paths, jvm)
+        && (args.include_unknown_version.unwrap_or(false) || jvm.major_version().is_some())
+}
+
+/// Collapses JVMs sharing a canonical path, keeping the first position and
+/// preferring the entry with a known vendor, then a known name
+fn dedup_by_realpath(jvms: Vec<Jvm>) -> Vec<Jvm> {
+    let score = |jvm: &Jvm| (!jvm.vendor.is_empty(), !jvm.name.is_empty());
+    let mut indices: HashMap<PathBuf, usize> = HashMap::new();
+    let mut result: Vec<Jvm> = vec![];
+    for jvm in jvms {
+        let real_path = fs::canonicalize(&jvm.path).unwrap_or_else(|_| PathBuf::from(&jvm.path));
+        match indices.get(&real_path) {
+            Some(&index) => {
+                if score(&jvm) > score(&result[index]) {
+                    result[index] = jvm;
+                }
+            },
+            None => {
+                indices.insert(real_path, result.len());
+                result.push(jvm);
+            }
+        }
+    }
+    result
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn get_jenv_root() -> Option<PathBuf> {
+    match std::env::var_os("JENV_ROOT") {
+        Some(root) => Some(PathBuf::from(root)),
+        None => Some(dirs::home_dir()?.join(".jenv"))
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn get_jenv_version(jenv_root: &Path) -> Option<String> {
+    // Same precedence as jenv: shell, then local, then global version
+    if let Ok(version) = std::env::var("JENV_VERSION") {
+        if !version.trim().is_empty() {
+            return Some(version.trim().to_string());
+        }
+    }
+
+    get_jenv_file_version(std::env::current_dir().ok().as_deref(), jenv_root)
+}
+
+// The local version of the closest `.java-version` in `dir` or its ancestors, then the global one
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn get_jenv_file_version(dir: Option<&Path>, jenv_root: &Path) -> Option<String> {
+    let local = dir.into_iter().flat_map(|dir| dir.ancestors()).map(|dir| dir.join(".java-version"));
+    let global = jenv_root.join("version");
+    local.chain([global]).find_map(|path| {
+        let version = fs::read_to_string(path).ok()?;
+        let version = version.lines().next()?.trim();
+        if version.is_empty() {
+            None
+        } else {
+            Some(version.to_string())
+        }
+    })
+}
 
+// The jenv root and the version it selects, unless it's the system JVM
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn get_jenv_selection() -> Option<(PathBuf, String)> {
+    let jenv_root = get_jenv_root()?;
+    match get_jenv_version(&jenv_root) {
+        Some(version) if version != "system" => Some((jenv_root, version)),
+        _ => None
+    }
+}
+
+/// Marks the JVM selected by jenv as the default, moving it to the front when `sort`
+/// is on. In discovery order, it is left where it was found
+fn mark_jenv_default(mut jvms: Vec<Jvm>, jenv_root: &Path, version: &str, sort: bool) -> Vec<Jvm> {
+    // jenv versions are symlinks to the JVM home
+    let target = fs::canonicalize(jenv_root.join("versions").join(version)).ok();
+    let default = jvms.iter().position(|jvm| match &target {
+        Some(target) => fs::canonicalize(&jvm.path).is_ok_and(|path| &path == target),
+        None => jvm.version == version
+    });
+
+    if let Some(index) = default {
+        jvms[index].is_default = true;
+        if sort {
+            let jvm = jvms.remove(index);
+            jvms.insert(0, jvm);
+        }
+    }
     jvms
 }
 
 
+/// Same as `run`, but performs the blocking scan on tokio's blocking thread pool
+#[cfg(feature = "async")]
+pub async fn run_async(args: MatchOptions) -> Vec<Jvm> {
+    match tokio::task::spawn_blocking(move || run(args)).await {
+        Ok(jvms) => jvms,
+        Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+        Err(_) => vec![]
+    }
+}
+
 #[cfg(any(target_os = "linux", target_os = "macos"))]
 fn get_operating_system() -> Option<OperatingSystem> {
     let output = Command::new("uname")
         .arg("-ps")
         .stdout(Stdio::piped())
-        .output().unwrap();
-
-    let stdout = String::from_utf8(output.stdout).unwrap();
-    let parts: Vec<String> =
-        stdout.split(" ").map(|s| s.to_string()).collect();
+        .output();
+    let stdout = output.ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .unwrap_or_default();
 
-    let os = trim_string(parts.get(0).unwrap().as_str());
-    let arch = trim_string(parts.get(1).unwrap().as_str());
+    let (os, arch) = match parse_uname(&stdout) {
+        Some((os, arch)) if !arch.eq_ignore_ascii_case("unknown") => (os, arch),
+        // uname is missing or malformed, or doesn't know the processor (e.g. on Debian)
+        parsed => {
+            let host_os = if cfg!(target_os = "macos") { "Darwin" } else { "Linux" };
+            (parsed.map_or(host_os, |(os, _)| os), std::env::consts::ARCH)
+        }
+    };
 
     let default_architecture =
         if os.eq_ignore_ascii_case("Darwin") {
-            if arch.eq_ignore_ascii_case("arm") {
+            if arch.eq_ignore_ascii_case("arm") || normalize_arch(arch) == "aarch64" {
                 "aarch64".to_string()
             } else {
                 "x86_64".to_string()
             }
         } else if os.eq_ignore_ascii_case("Linux") {
-            if arch.eq_ignore_ascii_case("x86_64") {
-                "x86_64".to_string()
-            } else if arch.eq_ignore_ascii_case("i386") {
-                "x86".to_string()
-            } else if arch.eq_ignore_ascii_case("i586") {
-                "x86".to_string()
-            } else if arch.eq_ignore_ascii_case("i686") {
-                "x86".to_string()
-            } else if arch.eq_ignore_ascii_case("aarch64") {
-                "aarch64".to_string()
-            } else if arch.eq_ignore_ascii_case("arm64") {
-                "arm64".to_string()
-            } else {
-                return None;
+            match normalize_arch(arch).as_str() {
+                known @ ("x86_64" | "x86" | "aarch64") => known.to_string(),
+                _ => return None
             }
         } else {
             return None;
@@ -134,7 +754,7 @@ fn get_operating_system() -> Option<OperatingSystem> {
             Ok(release_file) => release_file,
             Err(_error) => return None
         };
-        let properties = read(BufReader::new(release_file)).unwrap();
+        let properties = read(BufReader::new(release_file)).ok()?;
         name.push_str(properties.get("ID").unwrap_or(&"".to_string()).replace("\"", "").as_str());
     } else if os.eq_ignore_ascii_case("Darwin") {
         name.push_str("macOS");
@@ -148,18 +768,18 @@ fn get_operating_system() -> Option<OperatingSystem> {
 
 #[cfg(target_os = "windows")]
 fn get_operating_system() -> Option<OperatingSystem> {
-    let current_version = RegKey::predef(HKEY_LOCAL_MACHINE).open_subkey("SOFTWARE\\Microsoft\\Windows NT\\CurrentVersion").unwrap();
-    let name: String = current_version.get_value("ProductName").unwrap();
+    let current_version = RegKey::predef(HKEY_LOCAL_MACHINE).open_subkey("SOFTWARE\\Microsoft\\Windows NT\\CurrentVersion").ok()?;
+    let name: String = current_version.get_value("ProductName").ok()?;
 
-    let environment = RegKey::predef(HKEY_LOCAL_MACHINE).open_subkey("SYSTEM\\CurrentControlSet\\Control\\Session Manager\\Environment").unwrap();
-    let arch: String = environment.get_value("PROCESSOR_ARCHITECTURE").unwrap();
+    let environment = RegKey::predef(HKEY_LOCAL_MACHINE).open_subkey("SYSTEM\\CurrentControlSet\\Control\\Session Manager\\Environment").ok()?;
+    let arch: String = environment.get_value("PROCESSOR_ARCHITECTURE").ok()?;
     let default_architecture =
         if arch.eq_ignore_ascii_case("amd64") {
             "x86_64".to_string()
         } else if arch.eq_ignore_ascii_case("x86") {
             "x86".to_string()
         } else if arch.eq_ignore_ascii_case("arm64") {
-            "arm64".to_string()
+            "aarch64".to_string()
         } else {
             return None;
         };
@@ -170,16 +790,128 @@ fn get_operating_system() -> Option<OperatingSystem> {
     })
 }
 
-#[cfg(any(target_os = "linux", target_os = "macos"))]
-fn trim_string(value: &str) -> &str {
-    value.strip_suffix("\r\n")
-        .or(value.strip_suffix("\n"))
-        .unwrap_or(value)
+// JVMs can't be discovered from wasm, only the types are usable there
+#[cfg(target_arch = "wasm32")]
+fn get_operating_system() -> Option<OperatingSystem> {
+    None
+}
+
+#[cfg(target_arch = "wasm32")]
+fn scan_jvms(_os: &OperatingSystem, _cfg: &Config) -> impl Iterator<Item = Jvm> {
+    std::iter::empty()
+}
+
+/// Expands glob patterns in the custom paths to the directories they match.
+/// Plain paths are kept as-is and invalid patterns are skipped.
+#[cfg(feature = "glob")]
+fn expand_paths(paths: &[String]) -> Vec<String> {
+    paths.iter()
+        .flat_map(|path| {
+            if !path.contains(['*', '?', '[']) {
+                return vec![path.clone()];
+            }
+            match glob::glob(path) {
+                Ok(entries) => entries
+                    .filter_map(|entry| entry.ok())
+                    .filter(|entry| entry.is_dir())
+                    .filter_map(|entry| entry.to_str().map(|e| e.to_string()))
+                    .collect(),
+                Err(_err) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(pattern = path.as_str(), error = %_err, "skipping invalid JVM path pattern");
+                    vec![]
+                }
+            }
+        })
+        .collect()
+}
+
+#[cfg(not(feature = "glob"))]
+fn expand_paths(paths: &[String]) -> Vec<String> {
+    paths.to_vec()
 }
 
-#[cfg(target_os = "linux")]
 fn collate_jvms(os: &OperatingSystem, cfg: &Config) -> io::Result<Vec<Jvm>> {
-    let mut jvms = HashSet::new();
+    let env = env_jvms(&cfg.env_vars, &cfg.version_keys, |var| std::env::var_os(var));
+    collate_with_env_jvms(os, cfg, env)
+}
+
+fn collate_with_env_jvms(os: &OperatingSystem, cfg: &Config, env: Vec<Jvm>) -> io::Result<Vec<Jvm>> {
+    // Deduplicated in discovery order
+    let mut seen = HashSet::new();
+    let mut return_vec: Vec<Jvm> = scan_jvms(os, cfg)
+        .filter(|jvm| seen.insert(jvm.clone()))
+        .collect();
+
+    // Mark the scanned JVMs the environment points at, and add those the scans missed
+    for env_jvm in env {
+        let real_home = real_path(&env_jvm.path);
+        match return_vec.iter_mut().find(|jvm| real_path(&jvm.path) == real_home) {
+            Some(jvm) => jvm.from_env = env_jvm.from_env,
+            None => return_vec.push(env_jvm)
+        }
+    }
+    if cfg.sort {
+        return_vec.sort_by(|a, b| compare_boosting_architecture(a, b, &os.architecture, &cfg.vendor_priority));
+    }
+    Ok(return_vec)
+}
+
+// The kernel name and processor of `uname -ps` output - e.g. "Linux x86_64\n"
+#[cfg(any(target_os = "linux", target_os = "macos", test))]
+fn parse_uname(output: &str) -> Option<(&str, &str)> {
+    let mut parts = output.split_whitespace();
+    Some((parts.next()?, parts.next()?))
+}
+
+/// Resolve the `java` / `javac` links of the Nix profiles to their JDK home in the store
+#[cfg(target_os = "linux")]
+fn find_nix_homes() -> Vec<PathBuf> {
+    let mut bins = vec![PathBuf::from("/run/current-system/sw/bin")];
+    if let Some(home) = dirs::home_dir() {
+        bins.insert(0, home.join(".nix-profile").join("bin"));
+    }
+
+    let mut homes = Vec::new();
+    for bin in bins {
+        for exe in ["java", "javac"] {
+            // Store paths are deep and the links chain through several derivations,
+            // so only trust the home the fully resolved executable lives in
+            let home = match fs::canonicalize(bin.join(exe)) {
+                Ok(real) => real.parent().and_then(|p| p.parent()).map(|p| p.to_path_buf()),
+                Err(_) => None
+            };
+            if let Some(home) = home {
+                if !homes.contains(&home) {
+                    homes.push(home);
+                }
+            }
+        }
+    }
+    homes
+}
+
+/// The name of the store derivation a path lives in, without its hash - e.g. openjdk-17.0.8
+#[cfg(any(target_os = "linux", test))]
+fn nix_store_name(path: &Path) -> Option<String> {
+    let derivation = path.strip_prefix("/nix/store").ok()?.components().next()?;
+    let derivation = derivation.as_os_str().to_str()?;
+    derivation.split_once('-').map(|(_, name)| name.to_string())
+}
+
+#[cfg(target_os = "linux")]
+fn load_nix_jvm(home: PathBuf, version_keys: &[String]) -> Option<Jvm> {
+    let release_file = File::open(home.join("release")).ok()?;
+    let properties = read(BufReader::new(release_file)).ok()?;
+    let mut jvm = Jvm::from_release(&home, &properties, version_keys)?;
+    if let Some(name) = nix_store_name(&home) {
+        jvm.name = name;
+    }
+    Some(jvm)
+}
+
+#[cfg(target_os = "linux")]
+fn scan_jvms(os: &OperatingSystem, cfg: &Config) -> impl Iterator<Item = Jvm> {
     let dir_lookup = HashMap::from(
         [("ubuntu".to_string(), "/usr/lib/jvm".to_string()),
             ("debian".to_string(), "/usr/lib/jvm".to_string()),
@@ -187,259 +919,396 @@ fn collate_jvms(os: &OperatingSystem, cfg: &Config) -> io::Result<Vec<Jvm>> {
             ("centos".to_string(), "/usr/lib/jvm".to_string()),
             ("fedora".to_string(), "/usr/lib/jvm".to_string())]);
 
+    // Whether to follow the links in each directory, only custom paths may
     let path = dir_lookup.get(os.name.as_str());
-    if path.is_none() && cfg.paths.is_empty() {
-        return Ok(vec![]);
-    }
-    let mut paths = cfg.paths.to_vec();
-    paths.push(path.unwrap().to_string());
-
-    for path in paths {
-        for path in fs::read_dir(path)? {
-            let path = path.unwrap().path();
-            let metadata = fs::metadata(&path).unwrap();
-            let link = fs::read_link(&path);
-
-            if metadata.is_dir() && link.is_err() {
-                // Attempt to use release file, if not, attempt to build from folder name
-                let release_file = File::open(path.join("release"));
-                if release_file.is_ok() {
-                    // Collate required information
-                    let properties = read(BufReader::new(release_file.unwrap())).unwrap();
-                    let version = properties.get("JAVA_VERSION").unwrap_or(&"".to_string()).replace("\"", "");
-                    let architecture = properties.get("OS_ARCH").unwrap_or(&"".to_string()).replace("\"", "");
-                    let name = path.file_name().unwrap().to_str().unwrap().to_string();
-
-                    // Build JVM Struct
-                    let tmp_jvm = Jvm {
-                        version,
-                        architecture,
-                        name,
-                        path: path.to_str().unwrap().to_string(),
-                    };
-                    jvms.insert(tmp_jvm);
-                } else {
-                    let file_name = path.file_name().unwrap().to_str().unwrap();
-                    let parts: Vec<String> = file_name.split("-").map(|s| s.to_string()).collect();
-                    // Assuming four part or more form - e.g. "java-8-openjdk-amd64"
-                    if parts.len() < 3 || !parts.get(1).unwrap().to_string().eq("java") {
-                        continue;
-                    }
-
-                    let version = parts.get(1).unwrap().to_string();
-                    let mut architecture = parts.get(3).unwrap().to_string();
-                    architecture = architecture.replace("amd64", "x86_64");
-                    architecture = architecture.replace("i386", "x86");
-                    let name = file_name.to_string();
-
-                    // Build JVM Struct
-                    let tmp_jvm = Jvm {
-                        version,
-                        architecture,
-                        name,
-                        path: path.to_str().unwrap().to_string(),
-                    };
-                    jvms.insert(tmp_jvm);
-                }
-            }
+    let mut paths: Vec<(String, bool)> = expand_paths(&cfg.paths)
+        .into_iter()
+        .map(|path| (path, cfg.follow_symlinks))
+        .collect();
+    if let Some(path) = path {
+        paths.push((path.to_string(), false));
+    }
+
+    // Nix profiles are checked on every distribution, not only NixOS
+    let version_keys = cfg.version_keys.clone();
+    let nix_version_keys = cfg.version_keys.clone();
+    let mut seen = HashSet::new();
+    find_nix_homes()
+        .into_iter()
+        .filter_map(move |home| load_nix_jvm(home, &nix_version_keys))
+        .chain(paths
+            .into_iter()
+            // Skip directories that can't be read rather than failing the whole scan
+            .filter_map(|(path, follow_symlinks)| {
+                Some(fs::read_dir(path).ok()?.map(move |entry| (entry, follow_symlinks)))
+            })
+            .flatten()
+            .filter_map(|(entry, follow_symlinks)| {
+                let path = entry.ok()?.path();
+                let is_link = fs::read_link(&path).is_ok();
+                (follow_symlinks || !is_link).then_some(path)
+            })
+            // Links may point at a home found elsewhere in the scan
+            .filter(move |path| seen.insert(real_path(&path.to_string_lossy())))
+            .filter_map(move |path| load_jvm_dir(&path, &version_keys)))
+}
+
+#[cfg(target_os = "linux")]
+fn load_jvm_dir(path: &Path, version_keys: &[String]) -> Option<Jvm> {
+    let metadata = fs::metadata(path).ok()?;
+    let file_name = path.file_name()?.to_str()?.to_string();
+
+    if !metadata.is_dir() {
+        return None;
+    }
+
+    // Attempt to use release file, if not, attempt to build from folder name
+    let properties = File::open(path.join("release"))
+        .ok()
+        .and_then(|release_file| read(BufReader::new(release_file)).ok());
+    if let Some(properties) = properties {
+        Jvm::from_release(path, &properties, version_keys)
+    } else {
+        let parts: Vec<String> = file_name.split("-").map(|s| s.to_string()).collect();
+        // Assuming four part or more form - e.g. "java-8-openjdk-amd64"
+        if parts.len() < 4 || parts[0] != "java" {
+            return None;
         }
+
+        Jvm::from_home(path, parts[1].to_string(), normalize_arch(&parts[3]))
     }
-    let mut return_vec: Vec<Jvm> = jvms.into_iter().collect();
-    return_vec.sort_by(|a, b| compare_boosting_architecture(a, b, &os.architecture));
-    return Ok(return_vec);
 }
 
 #[cfg(target_os = "macos")]
-fn collate_jvms(os: &OperatingSystem, cfg: &Config) -> io::Result<Vec<Jvm>> {
+fn scan_jvms(os: &OperatingSystem, cfg: &Config) -> impl Iterator<Item = Jvm> {
     assert!(os.name.contains("macOS"));
-    let mut jvms = HashSet::new();
-    let mut paths = cfg.paths.to_vec();
+    let mut paths = expand_paths(&cfg.paths);
     paths.push("/Library/Java/JavaVirtualMachines".to_string());
-    for path in paths {
-        for path in fs::read_dir(path)? {
-            let path = path.unwrap().path();
-            let metadata = fs::metadata(&path)?;
-
-            if metadata.is_dir() {
-                // Attempt to load the Info PList
-                let info =
-                    Value::from_file(path.join("Contents/Info.plist"));
-
-                let info = match info {
-                    Ok(info) => info,
-                    Err(_error) => continue,
-                };
-                let name = info
-                    .as_dictionary()
-                    .and_then(|dict| dict.get("CFBundleName"))
-                    .and_then(|info_string| info_string.as_string());
-                let name = name.unwrap_or(&"".to_string()).replace("\"", "");
-
-                // Attempt to load the Release file into HashMap
-                let release_file = File::open(path.join("Contents/Home/release"));
-                let release_file = match release_file {
-                    Ok(release_file) => release_file,
-                    Err(_error) => continue,
-                };
-
-                // Collate required information
-                let properties = match read(BufReader::new(release_file)) {
-                    Ok(p) => p,
-                    Err(err) => return Err(io::Error::new(io::ErrorKind::Other, err.to_string()))
-                };
-                let version = properties.get("JAVA_VERSION").unwrap_or(&"".to_string()).replace("\"", "");
-                let architecture = properties.get("OS_ARCH").unwrap_or(&"".to_string()).replace("\"", "");
-
-                // Build JVM Struct
-                let tmp_jvm = Jvm {
-                    version,
-                    architecture,
-                    name,
-                    path: path.join("Contents/Home").to_str().unwrap().to_string(),
-                };
-                jvms.insert(tmp_jvm);
-            }
-        }
+    let version_keys = cfg.version_keys.clone();
+    paths
+        .into_iter()
+        .filter_map(|path| fs::read_dir(path).ok())
+        .flatten()
+        .filter_map(move |entry| load_bundle(&entry.ok()?.path(), &version_keys))
+}
+
+#[cfg(target_os = "macos")]
+fn load_bundle(path: &Path, version_keys: &[String]) -> Option<Jvm> {
+    if !fs::metadata(path).ok()?.is_dir() {
+        return None;
+    }
+
+    // Not every bundle keeps its home under Contents/Home
+    let home = find_bundle_home(path)?;
+
+    // Attempt to load the Info PList
+    let info =
+        Value::from_file(path.join("Contents/Info.plist"));
+    let name = match info {
+        Ok(info) => info
+            .as_dictionary()
+            .and_then(|dict| dict.get("CFBundleName"))
+            .and_then(|info_string| info_string.as_string())
+            .unwrap_or("")
+            .replace("\"", ""),
+        Err(_error) => path.file_name()?.to_string_lossy().to_string(),
+    };
+
+    // Attempt to load the Release file into HashMap, a JRE home keeps it in its parent
+    let release_file = File::open(home.join("release"))
+        .or_else(|_| File::open(home.parent().unwrap_or(&home).join("release")))
+        .ok()?;
+
+    let properties = read(BufReader::new(release_file)).ok()?;
+    Some(Jvm {
+        name,
+        ..Jvm::from_release(&home, &properties, version_keys)?
+    })
+}
+
+/// Finds the JVM home inside a macOS bundle: the first of Contents/Home, the bundle
+/// root, Contents/Home/jre or a direct subdirectory of the bundle containing bin/java
+#[cfg(any(target_os = "macos", test))]
+fn find_bundle_home(bundle: &Path) -> Option<PathBuf> {
+    let mut candidates = vec![
+        bundle.join("Contents/Home"),
+        bundle.to_path_buf(),
+        bundle.join("Contents/Home/jre")
+    ];
+    if let Ok(entries) = fs::read_dir(bundle) {
+        let mut children: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect();
+        children.sort();
+        candidates.extend(children);
     }
-    let mut return_vec: Vec<Jvm> = jvms.into_iter().collect();
-    return_vec.sort_by(|a, b| compare_boosting_architecture(a, b, &os.architecture));
-    return Ok(return_vec);
+    candidates.into_iter().find(|home| home.join("bin/java").is_file())
 }
 
+/// The JVM homes registered under the software keys of the registry, skipping
+/// the keys that can't be read
 #[cfg(target_os = "windows")]
-fn collate_jvms(os: &OperatingSystem, cfg: &Config) -> io::Result<Vec<Jvm>> {
-    assert!(os.name.contains("Windows"));
-    let mut jvms = HashSet::new();
+fn find_registry_homes() -> Vec<PathBuf> {
+    let mut homes = vec![];
 
     // Loop round software keys in the registry
-    let system = RegKey::predef(HKEY_LOCAL_MACHINE).open_subkey("SOFTWARE").unwrap();
-    for name in system.enum_keys().map(|x| x.unwrap()) {
-        let software: String = name.clone();
+    let system = match RegKey::predef(HKEY_LOCAL_MACHINE).open_subkey("SOFTWARE") {
+        Ok(system) => system,
+        Err(_) => return homes
+    };
+    for software in system.enum_keys().filter_map(|x| x.ok()) {
+        let jdks = match system.open_subkey(&software) {
+            Ok(key) => key,
+            Err(_) => continue
+        };
         // Find software with JDK key
-        for jdk in system.open_subkey(name).unwrap().enum_keys()
-                            .map(|x| x.unwrap())
+        for jdk in jdks.enum_keys()
+                            .filter_map(|x| x.ok())
                             .filter(|x| x.starts_with("JDK") || x.starts_with("Java Development Kit")) {
             // Next key should be JVM
-            for jvm in system.open_subkey(format!("{}\\{}", software, jdk)).unwrap().enum_keys().map(|x| x.unwrap()) {
+            let jvms = match system.open_subkey(format!("{}\\{}", software, jdk)) {
+                Ok(key) => key,
+                Err(_) => continue
+            };
+            for jvm in jvms.enum_keys().filter_map(|x| x.ok()) {
                 let mut jvm_path = String::new();
                 // Old style JavaSoftware entry
-                let java_home: Result<String, _> = system.open_subkey(format!("{}\\{}\\{}", software, jdk, jvm)).unwrap().get_value("JavaHome");
-                if java_home.is_ok() {
-                    jvm_path = java_home.unwrap();
+                let java_home = system.open_subkey(format!("{}\\{}\\{}", software, jdk, jvm))
+                    .and_then(|key| key.get_value::<String, _>("JavaHome"));
+                if let Ok(java_home) = java_home {
+                    jvm_path = java_home;
                 }
                 // Per JVM Entry - check for Hotspot or OpenJ9 entry
-                let hotspot_path: Result<RegKey, _> = system.open_subkey(format!("{}\\{}\\{}\\hotspot\\MSI", software, jdk, jvm));
-                if hotspot_path.is_ok() {
-                    jvm_path = hotspot_path.unwrap().get_value("Path").unwrap();
+                let hotspot_path = system.open_subkey(format!("{}\\{}\\{}\\hotspot\\MSI", software, jdk, jvm))
+                    .and_then(|key| key.get_value::<String, _>("Path"));
+                if let Ok(hotspot_path) = hotspot_path {
+                    jvm_path = hotspot_path;
                 }
-                let openj9_path: Result<RegKey, _> = system.open_subkey(format!("{}\\{}\\{}\\openj9\\MSI", software, jdk, jvm));
-                if openj9_path.is_ok() {
-                    jvm_path = openj9_path.unwrap().get_value("Path").unwrap();
+                let openj9_path = system.open_subkey(format!("{}\\{}\\{}\\openj9\\MSI", software, jdk, jvm))
+                    .and_then(|key| key.get_value::<String, _>("Path"));
+                if let Ok(openj9_path) = openj9_path {
+                    jvm_path = openj9_path;
                 }
                 jvm_path = jvm_path.strip_suffix("\\").unwrap_or(jvm_path.as_str()).to_string();
-
-                let path = Path::new(jvm_path.as_str()).join("release");
-                let release_file = File::open(path);
-                if release_file.is_ok() {
-                    jvms.insert(process_release_file(&jvm_path, release_file.unwrap()));
-                }
-            }
-        }
-    }
-    // Read from Custom JVM Location Paths
-    if !cfg.paths.is_empty() {
-        for path in &cfg.paths {
-            for path in fs::read_dir(path)? {
-                let jvm_path = path.unwrap().path();
-                let metadata = fs::metadata(&jvm_path).unwrap();
-
-                if metadata.is_dir() {
-                    let path = Path::new(jvm_path.to_str().unwrap()).join("release");
-                    let release_file = File::open(&path);
-                    if release_file.is_ok() {
-                        jvms.insert(process_release_file(&jvm_path.to_str().unwrap().to_string(), release_file.unwrap()));
-                    }
-                }
-
+                homes.push(PathBuf::from(jvm_path));
             }
         }
     }
-    let mut return_vec: Vec<Jvm> = jvms.into_iter().collect();
-    return_vec.sort_by(|a, b| compare_boosting_architecture(a, b, &os.architecture));
-    return Ok(return_vec);
+    homes
 }
 
 #[cfg(target_os = "windows")]
-fn process_release_file(jvm_path: &String, release_file: File) -> Jvm {
-    // Collate required information
-    let properties = read(BufReader::new(release_file)).unwrap();
-    let version = properties.get("JAVA_VERSION").unwrap_or(&"".to_string()).replace("\"", "");
-    let mut architecture = properties.get("OS_ARCH").unwrap_or(&"".to_string()).replace("\"", "");
-    architecture = architecture.replace("amd64", "x86_64");
-    architecture = architecture.replace("i386", "x86");
-    let implementor = properties.get("IMPLEMENTOR").unwrap_or(&"".to_string()).replace("\"", "");
-    let name = format!("{} - {}", implementor, version);
-
-    // Build JVM Struct
-    let tmp_jvm = Jvm {
-        version,
-        architecture,
-        name,
-        path: jvm_path.to_string(),
-    };
-    tmp_jvm
+fn scan_jvms(os: &OperatingSystem, cfg: &Config) -> impl Iterator<Item = Jvm> {
+    assert!(os.name.contains("Windows"));
+    let homes = find_registry_homes();
+
+    // Read from Custom JVM Location Paths, then from Scoop apps, e.g. scoop\apps\openjdk17\current,
+    // then from the default install directories of zip installs that aren't registered
+    let custom_homes = expand_paths(&cfg.paths)
+        .into_iter()
+        .filter_map(|path| fs::read_dir(path).ok())
+        .flatten()
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.is_dir());
+
+    let version_keys = cfg.version_keys.clone();
+    let mut seen = HashSet::new();
+    homes
+        .into_iter()
+        .chain(custom_homes)
+        .chain(find_scoop_homes())
+        .chain(find_program_files_homes())
+        .filter(move |home| seen.insert(fs::canonicalize(home).unwrap_or_else(|_| home.clone())))
+        .filter_map(move |home| {
+            let release_file = File::open(home.join("release")).ok()?;
+            process_release_file(&home.to_str()?.to_string(), release_file, &version_keys)
+        })
 }
 
-fn compare_boosting_architecture(a: &Jvm, b: &Jvm, default_arch: &String) -> Ordering {
-    let version_test = compare_version_values(&b.version, &a.version);
-    if version_test == Ordering::Equal {
-        if b.architecture != default_arch.as_str() && a.architecture == default_arch.as_str() {
-            return Ordering::Less;
-        }
-        if b.architecture == default_arch.as_str() && a.architecture != default_arch.as_str() {
-            return Ordering::Greater;
-        }
+/// Vendor directories under Program Files holding JDK homes, with the prefix of
+/// the home directory names (empty for any)
+#[cfg(target_os = "windows")]
+static PROGRAM_FILES_JDK_DIRS: [(&str, &str); 3] = [
+    ("Eclipse Adoptium", "jdk-"),
+    ("BellSoft", "LibericaJDK-"),
+    ("Semeru", "")
+];
+
+/// The JDK homes in the default install directories of some vendors, e.g.
+/// C:\Program Files\Eclipse Adoptium\jdk-17.0.8.7-hotspot
+#[cfg(target_os = "windows")]
+fn find_program_files_homes() -> Vec<PathBuf> {
+    let program_files = std::env::var_os("ProgramFiles")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("C:\\Program Files"));
+
+    let mut homes = Vec::new();
+    for (vendor, prefix) in PROGRAM_FILES_JDK_DIRS {
+        let entries = match fs::read_dir(program_files.join(vendor)) {
+            Ok(entries) => entries,
+            Err(_) => continue
+        };
+        homes.extend(entries
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| path.is_dir())
+            .filter(|path| path.file_name().and_then(|f| f.to_str()).is_some_and(|f| f.starts_with(prefix))));
     }
-    return version_test;
+    homes
 }
 
-fn filter_ver(ver: &Option<String>, jvm: &Jvm) -> bool {
-    if !ver.is_none() {
-        let version = ver.as_ref().unwrap();
-        if version.contains("+") {
-            let sanitised_version = version.replace("+", "");
-            let compare_jvm_version = get_compare_version(jvm, &sanitised_version);
-            let compare = compare_version_values(&compare_jvm_version, &sanitised_version);
-            if compare.is_lt() {
-                return false;
-            }
-        } else {
-            let compare_jvm_version = get_compare_version(jvm, version);
-            let compare = compare_version_values(&version, &compare_jvm_version);
-            if compare.is_ne() {
-                return false;
+/// The `current` directories of the Scoop apps, in the user and global install roots
+#[cfg(target_os = "windows")]
+fn find_scoop_homes() -> Vec<PathBuf> {
+    let mut homes = Vec::new();
+    for root in crate::scoop::scoop_roots() {
+        let apps = match fs::read_dir(root.join("apps")) {
+            Ok(apps) => apps,
+            Err(_) => continue
+        };
+        for app in apps.filter_map(|app| app.ok()) {
+            let current = app.path().join("current");
+            // `current` is a junction to the versioned directory, report that one instead
+            let home = match fs::read_link(&current) {
+                Ok(target) => app.path().join(target),
+                Err(_) => current
+            };
+            if home.is_dir() && !homes.contains(&home) {
+                homes.push(home);
             }
         }
     }
-    return true;
+    homes
 }
 
-fn compare_version_values(version1: &String, version2: &String) -> Ordering {
-    // Normalise old style versions - e.g. 1.8 -> 8, 1.9 -> 9
-    let mut normalised1= version1.strip_prefix("1.")
-        .unwrap_or(version1.as_str()).to_string();
-    let mut normalised2= version2.strip_prefix("1.")
-        .unwrap_or(version2.as_str()).to_string();
-    // Normalise old sub versions e.g. 1.8.0_292 -> 1.8.0.292
-    normalised1 = normalised1.replace("_", ".");
-    normalised2 = normalised2.replace("_", ".");
+#[cfg(target_os = "windows")]
+fn process_release_file(jvm_path: &String, release_file: File, version_keys: &[String]) -> Option<Jvm> {
+    let properties = read(BufReader::new(release_file)).ok()?;
+    let mut jvm = Jvm::from_release(Path::new(jvm_path), &properties, version_keys)?;
+    // Named like the registry entries, e.g. "Eclipse Adoptium - 17.0.8"
+    jvm.name = format!("{} - {}", jvm.vendor, jvm.version);
+    Some(jvm)
+}
+
+// Strips the build suffix from a JAVA_VERSION - e.g. 1.8.0_382-b05 -> 1.8.0_382, 17.0.8+7 -> 17.0.8
+fn core_version(version: &str) -> String {
+    version.split(['-', '+']).next().unwrap_or("").to_string()
+}
+
+// The first non-empty value of the version keys, as-is
+fn release_version(properties: &HashMap<String, String>, version_keys: &[String]) -> String {
+    version_keys.iter()
+        .filter_map(|key| properties.get(key))
+        .map(|value| value.replace("\"", ""))
+        .find(|value| !value.is_empty())
+        .unwrap_or_default()
+}
+
+fn get_release_date(properties: &HashMap<String, String>) -> Option<String> {
+    ["JAVA_VERSION_DATE", "BUILD_DATE"].iter()
+        .filter_map(|key| properties.get(*key))
+        .map(|value| value.replace("\"", ""))
+        .find(|value| !value.is_empty())
+}
+
+// Early-access builds are tagged `-ea` in their version, e.g. JAVA_RUNTIME_VERSION="21-ea+35-2513",
+// or in the build metadata after the build number, e.g. FULL_VERSION="22-beta+36-ea"
+fn is_early_access(properties: &HashMap<String, String>) -> bool {
+    ["JAVA_VERSION", "JAVA_RUNTIME_VERSION", "JAVA_FULL_VERSION", "FULL_VERSION", "SEMANTIC_VERSION", "JVM_VERSION", "IMPLEMENTOR_VERSION"].iter()
+        .filter_map(|key| properties.get(*key))
+        .any(|value| {
+            let value = value.replace("\"", "").to_lowercase();
+            value.match_indices("-ea").any(|(i, _)| match value[i + 3..].chars().next() {
+                None => true,
+                Some(c) => !c.is_alphanumeric()
+            })
+        })
+}
+
+fn compare_boosting_architecture(a: &Jvm, b: &Jvm, default_arch: &str, vendor_priority: &[String]) -> Ordering {
+    let version_test = compare_version_values(&b.version, &a.version);
+    if version_test != Ordering::Equal {
+        return version_test;
+    }
+    if b.architecture != default_arch && a.architecture == default_arch {
+        return Ordering::Less;
+    }
+    if b.architecture == default_arch && a.architecture != default_arch {
+        return Ordering::Greater;
+    }
+    vendor_rank(vendor_priority, a).cmp(&vendor_rank(vendor_priority, b))
+}
+
+// Lowercased once before sorting, so the comparisons don't redo it for every pair
+fn lowercase_vendors(vendor_priority: &[String]) -> Vec<String> {
+    vendor_priority.iter().map(|vendor| vendor.to_lowercase()).collect()
+}
+
+// The position of the first vendor in the lowercased list matching the JVM like
+// `filter_vendor` does, after the list if none does
+fn vendor_rank(vendor_priority: &[String], jvm: &Jvm) -> usize {
+    if vendor_priority.is_empty() {
+        return 0;
+    }
+    let (jvm_vendor, jvm_name) = (jvm.vendor.to_lowercase(), jvm.name.to_lowercase());
+    vendor_priority
+        .iter()
+        .position(|vendor| jvm_vendor.contains(vendor.as_str()) || jvm_name.contains(vendor.as_str()))
+        .unwrap_or(vendor_priority.len())
+}
+
+fn filter_ver(ver: &Option<String>, jvm: &Jvm) -> bool {
+    match ver {
+        Some(ver) => jvm.satisfies(ver),
+        None => true
+    }
+}
+
+// The requested parts must equal the leading parts of the JVM's version, so 17 and
+// 17.0 both match 17.0.8, while 17.0.8 matches a JVM reporting just 17 as 17.0.0
+fn matches_version(version: &str, jvm: &Jvm) -> bool {
+    let version = expand_update_version(version);
+    let sanitised_version = version.replace("+", "");
+    let requested = version_parts(&sanitised_version);
+    let compare_jvm_version = get_compare_version(jvm, &sanitised_version);
+    if version.contains("+") {
+        compare_jvm_version >= requested
+    } else {
+        compare_jvm_version == requested
+    }
+}
+
+// Expand the update shorthand - e.g. 8u292 -> 1.8.0_292
+fn expand_update_version(version: &str) -> String {
+    let is_number = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+    match version.trim_end_matches('+').split_once('u') {
+        Some((major, update)) if is_number(major) && is_number(update) => {
+            let suffix = if version.ends_with('+') { "+" } else { "" };
+            format!("1.{}.0_{}{}", major, update, suffix)
+        },
+        _ => version.to_string()
+    }
+}
+
+/// Normalises a version the way JVM versions are compared and matched: the update
+/// shorthand is expanded, the build suffix dropped, the `1.` prefix of old style
+/// versions removed and `_` replaced by `.` - e.g. 1.8.0_292-b10 -> 8.0.292, 8u292 ->
+/// 8.0.292, 17.0.8+7 -> 17.0.8
+pub fn normalize_version(version: &str) -> String {
+    let version = core_version(&expand_update_version(version.trim()));
+    version.strip_prefix("1.").unwrap_or(&version).replace('_', ".")
+}
+
+fn compare_version_values(version1: &str, version2: &str) -> Ordering {
+    let normalised1 = normalize_version(version1);
+    let normalised2 = normalize_version(version2);
 
     let count_version1: Vec<String> =
         normalised1.split(".").map(|s| s.to_string()).collect();
     let count_version2: Vec<String> =
         normalised2.split(".").map(|s| s.to_string()).collect();
 
-    let compare = Ordering::Equal;
     let max_size = std::cmp::max(count_version1.len(), count_version2.len());
 
     for i in 0..max_size {
@@ -449,8 +1318,9 @@ fn compare_version_values(version1: &String, version2: &String) -> Ordering {
         if count_version2.get(i).is_none(){
             return Ordering::Greater
         }
-        let version1_int = count_version1.get(i).unwrap().parse::<i32>().unwrap();
-        let version2_int = count_version2.get(i).unwrap().parse::<i32>().unwrap();
+        // Unparsable parts (e.g. of an empty version) order before any number
+        let version1_int = parse_version_part(count_version1.get(i).unwrap());
+        let version2_int = parse_version_part(count_version2.get(i).unwrap());
         if version1_int > version2_int {
             return Ordering::Greater
         } else if version1_int < version2_int {
@@ -459,46 +1329,1191 @@ fn compare_version_values(version1: &String, version2: &String) -> Ordering {
             continue;
         }
     }
-    return compare;
+    Ordering::Equal
+}
+
+// The leading digits of a version part - e.g. 17-ea -> 17
+fn parse_version_part(part: &str) -> Option<i32> {
+    let digits: String = part.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
+
+// The numeric parts of a version, with old style versions normalised the same way
+// as in compare_version_values - e.g. 1.8.0_292 -> [8, 0, 292], 1.8 -> [8]
+fn version_parts(version: &str) -> Vec<Option<i32>> {
+    normalize_version(version).split('.').map(parse_version_part).collect()
+}
+
+// As many parts of the JVM's version as the requested version has, missing ones being 0
+fn get_compare_version(jvm: &Jvm, version: &str) -> Vec<Option<i32>> {
+    let mut compare_version = version_parts(&jvm.version);
+    compare_version.resize(version_parts(version).len(), Some(0));
+    compare_version
+}
+
+fn filter_arch(arch: &Option<String>, jvm: &Jvm) -> bool {
+    match arch {
+        Some(arch) => normalize_arch(&jvm.architecture) == normalize_arch(arch),
+        None => true
+    }
 }
 
-fn get_compare_version(jvm: &Jvm, version: &String) -> String {
-    let version_count = version.matches('.').count();
-    let mut  jvm_version = jvm.version.clone();
+/// Maps the aliases used by release files, folder names and hosts to one name,
+/// e.g. `amd64` and `x64` to `x86_64`, or `arm64` to `aarch64`
+fn normalize_arch(arch: &str) -> String {
+    let arch = arch.to_ascii_lowercase();
+    match arch.as_str() {
+        "amd64" | "x64" => "x86_64".to_string(),
+        "arm64" => "aarch64".to_string(),
+        "i386" | "i586" | "i686" => "x86".to_string(),
+        _ => arch
+    }
+}
+
+/// The pointer width of a normalized architecture, `None` when it isn't known
+fn arch_bits(arch: &str) -> Option<u8> {
+    match arch {
+        "x86" | "arm" | "armv7" | "armhf" | "ppc" | "s390" | "sparc" | "mips" => Some(32),
+        "x86_64" | "aarch64" | "ppc64" | "ppc64le" | "s390x" | "sparcv9" | "riscv64" | "loongarch64" | "mips64" => Some(64),
+        _ => None
+    }
+}
 
-    // Normalise single digit compares for old style versions
-    if jvm.version.starts_with("1.") && version.matches('.').count() == 0 {
-        if !version.starts_with("1.") {
-            jvm_version = jvm_version.strip_prefix("1.")
-                .unwrap_or(jvm_version.as_str()).to_string();
+/// The first 4 KiB of the java launcher, enough for its executable header and, in
+/// practice, the ELF program interpreter. Empty when the launcher can't be read
+#[cfg(not(target_arch = "wasm32"))]
+fn launcher_header(home: &Path) -> Vec<u8> {
+    let java = if cfg!(windows) { "java.exe" } else { "java" };
+    let mut header = Vec::with_capacity(4096);
+    if let Ok(file) = File::open(home.join("bin").join(java)) {
+        // A short read still leaves what was read for the header checks
+        let _ = file.take(4096).read_to_end(&mut header);
+    }
+    header
+}
+
+// Falls back to the header of the java executable, e.g. for a 32-bit JDK installed on
+// 64-bit Windows without OS_ARCH in its release file
+#[cfg(not(target_arch = "wasm32"))]
+fn jvm_bits(arch: &str, header: &[u8]) -> Option<u8> {
+    arch_bits(arch).or_else(|| executable_bits(header))
+}
+
+/// The pointer width of an ELF, PE or Mach-O executable from its first bytes
+#[cfg(any(not(target_arch = "wasm32"), test))]
+fn executable_bits(header: &[u8]) -> Option<u8> {
+    if header.get(0..4)? == b"\x7fELF" {
+        return match header.get(4)? {
+            1 => Some(32),
+            2 => Some(64),
+            _ => None
+        };
+    }
+    if header.get(0..2)? == b"MZ" {
+        let pe = u32::from_le_bytes(header.get(0x3c..0x40)?.try_into().ok()?) as usize;
+        if header.get(pe..pe + 4)? != b"PE\0\0" {
+            return None;
         }
+        return match u16::from_le_bytes(header.get(pe + 4..pe + 6)?.try_into().ok()?) {
+            0x14c | 0x1c4 => Some(32),
+            0x8664 | 0xaa64 => Some(64),
+            _ => None
+        };
     }
+    match header.get(0..4)? {
+        [0xce, 0xfa, 0xed, 0xfe] | [0xfe, 0xed, 0xfa, 0xce] => Some(32),
+        [0xcf, 0xfa, 0xed, 0xfe] | [0xfe, 0xed, 0xfa, 0xcf] => Some(64),
+        _ => None
+    }
+}
 
-    let tmp_version: Vec<String> =
-        jvm_version.split_inclusive(".").map(|s| s.to_string()).collect();
-    let mut compare_version: String = String::new();
-    for i in 0..version_count + 1 {
-        compare_version.push_str(tmp_version.get(i).unwrap_or(&"".to_string()));
+fn filter_bits(bits: &Option<u8>, jvm: &Jvm) -> bool {
+    match bits {
+        Some(bits) => jvm.bits == Some(*bits),
+        None => true
     }
-    compare_version = compare_version.strip_suffix(".")
-        .unwrap_or(compare_version.as_str()).to_string();
-    compare_version
 }
 
-fn filter_arch(arch: &Option<String>, jvm: &Jvm) -> bool {
-    if !arch.is_none() {
-        if jvm.architecture != arch.as_ref().unwrap().to_string() {
-            return false;
+fn filter_exclude_paths(exclude_paths: &[String], jvm: &Jvm) -> bool {
+    if exclude_paths.is_empty() {
+        return true;
+    }
+    let home = real_path(&jvm.path);
+    !exclude_paths.iter().any(|prefix| home.starts_with(real_path(prefix)))
+}
+
+fn filter_ea(exclude_ea: &Option<bool>, jvm: &Jvm) -> bool {
+    !(exclude_ea.unwrap_or(false) && jvm.early_access)
+}
+
+fn filter_libc(libc: &Option<String>, jvm: &Jvm) -> bool {
+    match (libc, &jvm.libc) {
+        (Some(libc), Some(jvm_libc)) => libc.eq_ignore_ascii_case(jvm_libc),
+        _ => true
+    }
+}
+
+fn filter_lts(lts_only: &Option<bool>, jvm: &Jvm) -> bool {
+    !lts_only.unwrap_or(false)
+        || jvm.major_version().is_some_and(|major| LTS_MAJOR_VERSIONS.contains(&major))
+}
+
+fn filter_javafx(javafx_only: &Option<bool>, jvm: &Jvm) -> bool {
+    !javafx_only.unwrap_or(false) || jvm.has_javafx
+}
+
+// The C library of the JVM's launcher, from the dynamic loader its header requests
+#[cfg(target_os = "linux")]
+fn detect_libc(header: &[u8]) -> Option<String> {
+    let interpreter = elf_interpreter(header)?;
+    if interpreter.contains("ld-musl") {
+        Some("musl".to_string())
+    } else if interpreter.contains("ld-linux") {
+        Some("glibc".to_string())
+    } else {
+        None
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn detect_libc(_header: &[u8]) -> Option<String> {
+    None
+}
+
+/// The program interpreter (PT_INTERP) of an ELF executable - e.g. /lib/ld-musl-x86_64.so.1
+#[cfg(any(target_os = "linux", test))]
+fn elf_interpreter(elf: &[u8]) -> Option<String> {
+    if elf.get(0..4)? != b"\x7fELF" {
+        return None;
+    }
+    let is_64 = *elf.get(4)? == 2;
+    let is_le = *elf.get(5)? == 1;
+    let read = |offset: usize, size: usize| -> Option<usize> {
+        let bytes = elf.get(offset..offset + size)?;
+        let mut value: u64 = 0;
+        for i in 0..size {
+            let byte = if is_le { bytes[size - 1 - i] } else { bytes[i] };
+            value = (value << 8) | byte as u64;
+        }
+        usize::try_from(value).ok()
+    };
+
+    let (phoff, phentsize, phnum) = if is_64 {
+        (read(0x20, 8)?, read(0x36, 2)?, read(0x38, 2)?)
+    } else {
+        (read(0x1c, 4)?, read(0x2a, 2)?, read(0x2c, 2)?)
+    };
+    for i in 0..phnum {
+        let header = phoff + i * phentsize;
+        // PT_INTERP
+        if read(header, 4)? != 3 {
+            continue;
         }
+        let (offset, size) = if is_64 {
+            (read(header + 0x08, 8)?, read(header + 0x20, 8)?)
+        } else {
+            (read(header + 0x04, 4)?, read(header + 0x10, 4)?)
+        };
+        let interpreter = elf.get(offset..offset + size)?;
+        return Some(String::from_utf8_lossy(interpreter).trim_end_matches('\0').to_string());
+    }
+    None
+}
+
+// JavaFX ships as jmods in full JDKs (jmods/javafx.base.jmod), or with a
+// javafx.properties under lib/ (jre/lib/ for JDK 8). Checks those few files only
+fn has_javafx(home: &Path) -> bool {
+    ["jmods/javafx.base.jmod", "lib/javafx.properties", "jre/lib/javafx.properties"]
+        .iter()
+        .any(|marker| home.join(marker).is_file())
+}
+
+fn filter_vendor(vendor: &Option<String>, jvm: &Jvm) -> bool {
+    match vendor {
+        Some(vendor) => {
+            let vendor = vendor.to_lowercase();
+            jvm.vendor.to_lowercase().contains(&vendor) || jvm.name.to_lowercase().contains(&vendor)
+        },
+        None => true
     }
-    return true;
 }
 
 fn filter_name(name: &Option<String>, jvm: &Jvm) -> bool {
-    if !name.is_none() {
-        if jvm.name != name.as_ref().unwrap().to_string() {
-            return false;
+    match name {
+        Some(name) => jvm.name == *name,
+        None => true
+    }
+}
+
+// Compiled once per discovery, `Some(None)` when the pattern is invalid and matches nothing
+fn compile_name_regex(name_regex: &Option<String>) -> Option<Option<fancy_regex::Regex>> {
+    let pattern = name_regex.as_ref()?;
+    match fancy_regex::Regex::new(pattern) {
+        Ok(regex) => Some(Some(regex)),
+        Err(_err) => {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(pattern = pattern.as_str(), error = %_err, "invalid JVM name regex, matching nothing");
+            Some(None)
+        }
+    }
+}
+
+fn filter_name_regex(name_regex: &Option<fancy_regex::Regex>, jvm: &Jvm) -> bool {
+    match name_regex {
+        Some(regex) => regex.is_match(&jvm.name).unwrap_or(false),
+        None => false
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // A scan of just these paths, ignoring the environment
+    fn config(paths: Vec<String>) -> Config {
+        Config::from_options(&MatchOptions {
+            paths,
+            env_vars: Some(vec![]),
+            ..Default::default()
+        })
+    }
+
+    fn jvm(version: &str) -> Jvm {
+        Jvm {
+            version: version.to_string(),
+            full_version: version.to_string(),
+            name: String::new(),
+            vendor: String::new(),
+            architecture: "x86_64".to_string(),
+            bits: Some(64),
+            path: String::new(),
+            release_date: None,
+            early_access: false,
+            has_javafx: false,
+            libc: None,
+            is_default: false,
+            from_env: None,
+            embedded: false
+        }
+    }
+
+    // An x86_64 host for the scans in these tests
+    fn test_os() -> OperatingSystem {
+        OperatingSystem {
+            name: String::new(),
+            architecture: "x86_64".to_string()
+        }
+    }
+
+    // Writes the release file of `home`, quoting each value like a real JDK does
+    fn write_release(home: &Path, props: &[(&str, &str)]) {
+        fs::create_dir_all(home).unwrap();
+        let release: String = props.iter().map(|(key, value)| format!("{}=\"{}\"\n", key, value)).collect();
+        fs::write(home.join("release"), release).unwrap();
+    }
+
+    fn matches(filter: &str, version: &str) -> bool {
+        filter_ver(&Some(filter.to_string()), &jvm(version))
+    }
+
+    #[test]
+    fn test_filter_ver_update() {
+        assert!(matches("1.8.0_292", "1.8.0_292"));
+        assert!(!matches("1.8.0_292", "1.8.0_301"));
+        assert!(!matches("1.8.0_301", "1.8.0_292"));
+        assert!(matches("8u292", "1.8.0_292"));
+        assert!(!matches("8u292", "1.8.0_301"));
+        assert!(matches("8u292+", "1.8.0_301"));
+        assert!(!matches("8u301+", "1.8.0_292"));
+        assert!(matches("8", "1.8.0_292"));
+    }
+
+    #[test]
+    fn test_filter_ver_forms() {
+        let forms = ["17", "17.0", "17.0.8", "1.8", "8", "1.8.0_292"];
+        // Whether the filter (row) matches a JVM reporting each form (column)
+        let expected = [
+            [true, true, true, false, false, false],
+            [true, true, true, false, false, false],
+            [false, false, true, false, false, false],
+            [false, false, false, true, true, true],
+            [false, false, false, true, true, true],
+            [false, false, false, false, false, true],
+        ];
+        for (filter, row) in forms.iter().zip(expected) {
+            for (version, expected) in forms.iter().zip(row) {
+                assert_eq!(matches(filter, version), expected, "{} against {}", filter, version);
+            }
+        }
+
+        assert!(matches("17.0.0", "17"));
+        assert!(matches("17.0+", "17"));
+        assert!(matches("17.0.8+", "17.0.10"));
+        assert!(!matches("17.0.10+", "17.0.8"));
+        assert!(matches("1.8+", "17.0.8"));
+        assert!(!matches("17", "1.7.0_80"));
+    }
+
+    #[test]
+    fn test_feature_release_minimums() {
+        // Legacy 1.x and modern versions against each feature release minimum
+        let cases = [
+            ("8+", "1.8.0_292", true),
+            ("8+", "1.8.0", true),
+            ("8+", "1.8", true),
+            ("8+", "1.7.0_80", false),
+            ("8+", "9.0.4", true),
+            ("8+", "11.0.20", true),
+            ("8+", "21.0.1", true),
+            ("11+", "1.8.0_292", false),
+            ("11+", "10.0.2", false),
+            ("11+", "11", true),
+            ("11+", "11.0.20", true),
+            ("11+", "17.0.8", true),
+            ("17+", "1.8.0_392", false),
+            ("17+", "16.0.2", false),
+            ("17+", "17", true),
+            ("17+", "17.0.8", true),
+            ("17+", "21.0.1", true),
+            ("21+", "1.8.0_292", false),
+            ("21+", "17.0.10", false),
+            ("21+", "21", true),
+            ("21+", "21.0.1", true),
+            ("21+", "22", true)
+        ];
+        for (filter, version, expected) in cases {
+            assert_eq!(matches(filter, version), expected, "{} against {}", filter, version);
+        }
+        // The legacy form of the minimum is the same
+        assert!(matches("1.8+", "1.8.0_292"));
+        assert!(matches("1.8+", "17.0.8"));
+        assert!(!matches("1.8+", "1.7.0_80"));
+
+        // As parsed from user input, e.g. `Java 21+`
+        let spec = MatchOptions::parse("21+").version.unwrap();
+        assert!(jvm("21.0.1").satisfies(&spec));
+        assert!(!jvm("1.8.0_292").satisfies(&spec));
+        assert!(jvm("1.8.0_292").satisfies("21+, 8+"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_dedup_by_realpath() {
+        let dir = std::env::temp_dir().join(format!("where-is-it-realpath-{}", std::process::id()));
+        let home = dir.join("jdk-17");
+        let link = dir.join("default-java");
+        fs::create_dir_all(&home).unwrap();
+        std::os::unix::fs::symlink(&home, &link).unwrap();
+
+        let mut linked = jvm("17.0.8");
+        linked.path = link.to_str().unwrap().to_string();
+        linked.name = "default-java".to_string();
+        let mut real = jvm("17.0.8");
+        real.path = home.to_str().unwrap().to_string();
+        real.name = "jdk-17".to_string();
+        real.vendor = "Eclipse Adoptium".to_string();
+        let mut other = jvm("11.0.2");
+        other.path = dir.join("jdk-11").to_str().unwrap().to_string();
+
+        let deduped = dedup_by_realpath(vec![linked, real.clone(), other.clone()]);
+        assert_eq!(deduped, vec![real, other]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_filter_ver_list() {
+        assert!(!matches("11,17", "1.8.0_292"));
+        assert!(matches("11,17", "11.0.20"));
+        assert!(matches("11,17", "17.0.8"));
+        assert!(!matches("11,17", "21.0.1"));
+        assert!(matches("11, ,17,", "17.0.8"));
+
+        assert!(matches("11+,8", "1.8.0_292"));
+        assert!(!matches("11+,8", "9.0.4"));
+        assert!(matches("11+,8", "11.0.20"));
+        assert!(matches("11+,8", "21.0.1"));
+    }
+
+    #[test]
+    fn test_early_access() {
+        let ea = read("JAVA_VERSION=\"21\"\nJAVA_RUNTIME_VERSION=\"21-ea+35-2513\"\n".as_bytes()).unwrap();
+        let ga = read("JAVA_VERSION=\"21\"\nJAVA_RUNTIME_VERSION=\"21+35-2513\"\n".as_bytes()).unwrap();
+        assert!(is_early_access(&ea));
+        assert!(!is_early_access(&ga));
+
+        // Only tagged in the build metadata, and not fooled by words starting with "ea"
+        let ea_build = read("JAVA_VERSION=\"22\"\nFULL_VERSION=\"22-beta+36-ea\"\n".as_bytes()).unwrap();
+        let eabi = read("JAVA_VERSION=\"21\"\nIMPLEMENTOR_VERSION=\"Build-eabi-21+35\"\n".as_bytes()).unwrap();
+        assert!(is_early_access(&ea_build));
+        assert!(!is_early_access(&eabi));
+
+        let mut jvm = jvm("21");
+        jvm.early_access = is_early_access(&ea);
+        assert!(!filter_ea(&Some(true), &jvm));
+        assert!(filter_ea(&Some(false), &jvm));
+        assert!(filter_ea(&None, &jvm));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_collate_skips_unreadable_entries() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("where-is-it-unreadable-{}", std::process::id()));
+        let home = dir.join("jdk-17");
+        let locked = dir.join("locked");
+        fs::create_dir_all(locked.join("inner")).unwrap();
+        write_release(&home, &[("JAVA_VERSION", "17.0.8"), ("OS_ARCH", "x86_64")]);
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+        std::os::unix::fs::symlink(dir.join("missing"), dir.join("dangling")).unwrap();
+        // Permissions don't stop root (or CAP_DAC_OVERRIDE), there's nothing to test then
+        if fs::read_dir(&locked).is_ok() {
+            fs::remove_dir_all(&dir).unwrap();
+            return;
+        }
+
+        let os = test_os();
+        let cfg = config(vec![
+            dir.join("does-not-exist").to_str().unwrap().to_string(),
+            locked.to_str().unwrap().to_string(),
+            dir.to_str().unwrap().to_string()
+        ]);
+        // The default locations are scanned too, only the homes made here count
+        let jvms: Vec<Jvm> = collate_jvms(&os, &cfg)
+            .unwrap()
+            .into_iter()
+            .filter(|jvm| jvm.path.starts_with(dir.to_str().unwrap()))
+            .collect();
+
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(jvms.len(), 1);
+        assert_eq!(jvms[0].version, "17.0.8");
+        assert_eq!(jvms[0].name, "jdk-17");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_unknown_version() {
+        let dir = std::env::temp_dir().join(format!("where-is-it-unknown-{}", std::process::id()));
+        write_release(&dir.join("jdk-17"), &[("JAVA_VERSION", "17.0.8"), ("OS_ARCH", "x86_64")]);
+        write_release(&dir.join("custom-jdk"), &[("IMPLEMENTOR", "Custom"), ("OS_ARCH", "x86_64")]);
+
+        let os = test_os();
+        let cfg = config(vec![dir.to_str().unwrap().to_string()]);
+        let jvms: Vec<Jvm> = collate_jvms(&os, &cfg)
+            .unwrap()
+            .into_iter()
+            .filter(|jvm| jvm.path.starts_with(dir.to_str().unwrap()))
+            .collect();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(jvms.len(), 2);
+        assert_eq!(jvms[1].name, "custom-jdk");
+        assert!(jvms[1].version.is_empty());
+
+        let include = MatchOptions {
+            include_unknown_version: Some(true),
+            ..Default::default()
+        };
+        assert!(!filter_jvm(&MatchOptions::default(), &None, &jvms[1]));
+        assert!(filter_jvm(&include, &None, &jvms[1]));
+        assert!(filter_jvm(&MatchOptions::default(), &None, &jvms[0]));
+        assert!(!filter_jvm(&MatchOptions { version: Some("17".to_string()), ..include }, &None, &jvms[1]));
+    }
+
+    #[test]
+    fn test_has_javafx() {
+        let dir = std::env::temp_dir().join(format!("where-is-it-javafx-{}", std::process::id()));
+        let full = dir.join("full");
+        let legacy = dir.join("legacy");
+        let plain = dir.join("plain");
+        fs::create_dir_all(full.join("jmods")).unwrap();
+        fs::create_dir_all(legacy.join("lib")).unwrap();
+        fs::create_dir_all(plain.join("lib")).unwrap();
+        fs::write(full.join("jmods/javafx.base.jmod"), "").unwrap();
+        fs::write(legacy.join("lib/javafx.properties"), "").unwrap();
+        fs::write(plain.join("lib/modules"), "").unwrap();
+
+        let (full, legacy, plain) = (has_javafx(&full), has_javafx(&legacy), has_javafx(&plain));
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(full);
+        assert!(legacy);
+        assert!(!plain);
+    }
+
+    #[test]
+    fn test_core_version() {
+        assert_eq!(core_version("1.8.0_382-b05"), "1.8.0_382");
+        assert_eq!(core_version("17.0.8+7"), "17.0.8");
+        assert_eq!(core_version("17.0.8.1"), "17.0.8.1");
+        assert_eq!(core_version("21-ea"), "21");
+        assert_eq!(core_version(""), "");
+
+        let mut jvm = jvm(&core_version("1.8.0_382-b05"));
+        assert!(jvm.satisfies("1.8.0_382"));
+        assert!(jvm.satisfies("8u382"));
+        jvm.version = core_version("17.0.8.1");
+        assert!(jvm.satisfies("17.0.8"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_release_file_build_suffix() {
+        let dir = std::env::temp_dir().join(format!("where-is-it-suffix-{}", std::process::id()));
+        write_release(&dir.join("jdk8"), &[("JAVA_VERSION", "1.8.0_382-b05"), ("OS_ARCH", "x86_64")]);
+        let jvm = load_jvm_dir(&dir.join("jdk8"), &config(vec![]).version_keys);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let jvm = jvm.unwrap();
+        assert_eq!(jvm.version, "1.8.0_382");
+        assert_eq!(jvm.full_version, "1.8.0_382-b05");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_vendor_version_key() {
+        let dir = std::env::temp_dir().join(format!("where-is-it-semeru-{}", std::process::id()));
+        write_release(&dir.join("semeru"), &[("SEMERU_VERSION", "17.0.8.1"), ("OS_ARCH", "x86_64")]);
+
+        let default_keys = load_jvm_dir(&dir.join("semeru"), &config(vec![]).version_keys);
+        let vendor_keys = load_jvm_dir(&dir.join("semeru"), &["JAVA_VERSION".to_string(), "SEMERU_VERSION".to_string()]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(default_keys.unwrap().version, "");
+        assert_eq!(vendor_keys.unwrap().version, "17.0.8.1");
+    }
+
+    #[test]
+    fn test_inspect() {
+        let dir = std::env::temp_dir().join(format!("where-is-it-inspect-{}", std::process::id()));
+        write_release(&dir.join("jdk-17"), &[("JAVA_VERSION", "17.0.8"), ("OS_ARCH", "amd64")]);
+
+        let jvm = inspect(dir.join("jdk-17").to_str().unwrap()).unwrap();
+        assert_eq!(jvm.version, "17.0.8");
+        assert_eq!(jvm.architecture, "x86_64");
+        assert_eq!(jvm.name, "jdk-17");
+        assert!(inspect(dir.to_str().unwrap()).is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_inspect_all() {
+        let dir = std::env::temp_dir().join(format!("where-is-it-inspect-all-{}", std::process::id()));
+        fs::create_dir_all(dir.join("not-a-jdk")).unwrap();
+        write_release(&dir.join("jdk-11"), &[("JAVA_VERSION", "11.0.20"), ("OS_ARCH", "amd64")]);
+        write_release(&dir.join("jdk-21"), &[("JAVA_VERSION", "21.0.1"), ("OS_ARCH", "x86_64")]);
+
+        let homes: Vec<String> = ["jdk-11", "not-a-jdk", "jdk-21"]
+            .iter()
+            .map(|home| dir.join(home).to_str().unwrap().to_string())
+            .collect();
+        let jvms = inspect_all(&homes);
+        let names: Vec<&str> = jvms.iter().map(|jvm| jvm.name.as_str()).collect();
+        assert_eq!(names, vec!["jdk-21", "jdk-11"]);
+        assert_eq!(jvms[1].architecture, "x86_64");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_inspect_tree() {
+        let dir = std::env::temp_dir().join(format!("where-is-it-tree-{}", std::process::id()));
+        let java = if cfg!(windows) { "java.exe" } else { "java" };
+        let homes = [dir.join("Tool/runtime"), dir.join("Other.app/Contents/runtime/Contents/Home")];
+        for home in &homes {
+            fs::create_dir_all(home.join("bin")).unwrap();
+            fs::write(home.join("bin").join(java), "").unwrap();
+            write_release(home, &[("JAVA_VERSION", "17.0.8"), ("OS_ARCH", "x86_64")]);
+        }
+        // A release file without a java executable isn't a runtime
+        write_release(&dir.join("Docs"), &[("JAVA_VERSION", "21")]);
+
+        let jvms = inspect_tree(dir.to_str().unwrap());
+        fs::remove_dir_all(&dir).unwrap();
+
+        let paths: Vec<PathBuf> = jvms.iter().map(|jvm| PathBuf::from(&jvm.path)).collect();
+        assert_eq!(paths, vec![homes[1].clone(), homes[0].clone()]);
+        assert!(jvms.iter().all(|jvm| jvm.embedded && jvm.version == "17.0.8"));
+    }
+
+    #[test]
+    fn test_unsorted() {
+        let dir = std::env::temp_dir().join(format!("where-is-it-unsorted-{}", std::process::id()));
+        for version in ["11.0.20", "21.0.1", "17.0.8"] {
+            let home = dir.join(format!("jdk-{}", version));
+            write_release(&home, &[("JAVA_VERSION", version), ("OS_ARCH", "x86_64")]);
+        }
+        let os = test_os();
+        let paths = vec![dir.to_str().unwrap().to_string()];
+        let versions = |jvms: Vec<Jvm>| -> Vec<String> {
+            jvms.into_iter()
+                .filter(|jvm| jvm.path.starts_with(dir.to_str().unwrap()))
+                .map(|jvm| jvm.version)
+                .collect()
+        };
+
+        let mut cfg = config(paths);
+        let sorted = versions(collate_jvms(&os, &cfg).unwrap());
+        cfg.sort = false;
+        let scanned = versions(scan_jvms(&os, &cfg).collect());
+        let unsorted = versions(collate_jvms(&os, &cfg).unwrap());
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(sorted, vec!["21.0.1", "17.0.8", "11.0.20"]);
+        assert_eq!(unsorted, scanned);
+    }
+
+    #[test]
+    fn test_highest_only() {
+        let dir = std::env::temp_dir().join(format!("where-is-it-highest-{}", std::process::id()));
+        for version in ["17.0.2", "17.0.8", "21.0.1"] {
+            let home = dir.join(format!("jdk-{}", version));
+            write_release(&home, &[("JAVA_VERSION", version), ("IMPLEMENTOR", "Where Is It Test"), ("OS_ARCH", "x86_64")]);
+        }
+
+        let os = test_os();
+        let jvms = collate_jvms(&os, &config(vec![dir.to_str().unwrap().to_string()])).unwrap();
+        let options = |version: &str, highest_only: bool| MatchOptions {
+            vendor: Some("Where Is It Test".to_string()),
+            version: Some(version.to_string()),
+            highest_only: Some(highest_only),
+            ..Default::default()
+        };
+        let versions = |jvms: Vec<Jvm>, options: MatchOptions| -> Vec<String> {
+            select_jvms(jvms, &options, &os.architecture, None).into_iter().map(|jvm| jvm.version).collect()
+        };
+        // In discovery order, the highest still wins over the first found
+        let mut unsorted = jvms.clone();
+        unsorted.reverse();
+        let (all, newest, newest_any, newest_unsorted) = (
+            versions(jvms.clone(), options("17", false)),
+            versions(jvms.clone(), options("17", true)),
+            versions(jvms.clone(), options("11+", true)),
+            versions(unsorted, MatchOptions { sort: Some(false), ..options("11+", true) })
+        );
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(all, vec!["17.0.8", "17.0.2"]);
+        assert_eq!(newest, vec!["17.0.8"]);
+        assert_eq!(newest_any, vec!["21.0.1"]);
+        assert_eq!(newest_unsorted, vec!["21.0.1"]);
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    #[test]
+    fn test_highest_only_with_jenv() {
+        let dir = std::env::temp_dir().join(format!("where-is-it-highest-jenv-{}", std::process::id()));
+        for version in ["17.0.8", "21.0.1"] {
+            let home = dir.join(format!("jdk-{}", version));
+            write_release(&home, &[("JAVA_VERSION", version), ("OS_ARCH", "x86_64")]);
+        }
+        // jenv selects the older JDK globally
+        let jenv_root = dir.join(".jenv");
+        fs::create_dir_all(jenv_root.join("versions")).unwrap();
+        std::os::unix::fs::symlink(dir.join("jdk-17.0.8"), jenv_root.join("versions").join("17.0")).unwrap();
+        fs::write(jenv_root.join("version"), "17.0\n").unwrap();
+
+        let os = test_os();
+        let jvms: Vec<Jvm> = collate_jvms(&os, &config(vec![dir.to_str().unwrap().to_string()]))
+            .unwrap()
+            .into_iter()
+            .filter(|jvm| jvm.path.starts_with(dir.to_str().unwrap()))
+            .collect();
+        let jenv = get_jenv_file_version(None, &jenv_root).map(|version| (jenv_root.clone(), version));
+        let select = |highest_only: bool, sort: bool| -> Vec<(String, bool)> {
+            let options = MatchOptions { highest_only: Some(highest_only), sort: Some(sort), ..Default::default() };
+            select_jvms(jvms.clone(), &options, &os.architecture, jenv.clone())
+                .into_iter()
+                .map(|jvm| (jvm.version, jvm.is_default))
+                .collect()
+        };
+        let (highest, all, unsorted) = (select(true, true), select(false, true), select(false, false));
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(highest, vec![("21.0.1".to_string(), false)]);
+        assert_eq!(all, vec![("17.0.8".to_string(), true), ("21.0.1".to_string(), false)]);
+        // Discovery order is kept, only the flag is set
+        assert_eq!(unsorted, vec![("21.0.1".to_string(), false), ("17.0.8".to_string(), true)]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_write_jsonl() {
+        let dir = std::env::temp_dir().join(format!("where-is-it-jsonl-{}", std::process::id()));
+        for version in ["17.0.8", "21.0.1"] {
+            let home = dir.join(format!("jdk-{}", version));
+            write_release(&home, &[("JAVA_VERSION", version), ("IMPLEMENTOR", "Where Is It Test"), ("OS_ARCH", "x86_64")]);
+        }
+
+        let mut out = vec![];
+        let args = MatchOptions {
+            paths: vec![dir.to_str().unwrap().to_string()],
+            env_vars: Some(vec![]),
+            vendor: Some("Where Is It Test".to_string()),
+            ..Default::default()
+        };
+        write_jsonl(args, &mut out).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        let mut versions: Vec<String> = out
+            .lines()
+            .map(|line| serde_json::from_str::<Jvm>(line).unwrap().version)
+            .collect();
+        versions.sort();
+        assert!(out.ends_with('\n'));
+        assert_eq!(versions, vec!["17.0.8", "21.0.1"]);
+    }
+
+    #[test]
+    fn test_release_property() {
+        let dir = std::env::temp_dir().join(format!("where-is-it-property-{}", std::process::id()));
+        write_release(&dir.join("jdk-17"), &[("JAVA_VERSION", "17.0.8"), ("MODULES", "java.base java.logging")]);
+
+        let jvm = inspect(dir.join("jdk-17").to_str().unwrap()).unwrap();
+        assert_eq!(jvm.release_property("MODULES"), Some("java.base java.logging".to_string()));
+        assert_eq!(jvm.release_property("OS_NAME"), None);
+
+        // A copy moved to another home doesn't keep the properties read for this one
+        let mut missing = jvm.clone();
+        missing.path = dir.to_str().unwrap().to_string();
+        assert_eq!(missing.release_property("MODULES"), None);
+
+        // Read once, even if the file changes afterwards
+        fs::remove_file(dir.join("jdk-17/release")).unwrap();
+        assert_eq!(jvm.release_property("MODULES"), Some("java.base java.logging".to_string()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_env_jvms() {
+        let dir = std::env::temp_dir().join(format!("where-is-it-env-{}", std::process::id()));
+        write_release(&dir.join("jdk-21"), &[("JAVA_VERSION", "21.0.1"), ("OS_ARCH", "x86_64")]);
+        let home = dir.join("jdk-21").to_str().unwrap().to_string();
+
+        // Both variables point at the same home, the process environment is left alone
+        let env_vars = vec!["WHERE_IS_IT_TEST_JDK_A".to_string(), "WHERE_IS_IT_TEST_JDK_B".to_string()];
+        let version_keys = config(vec![]).version_keys;
+        let lookup = |var: &str| var.starts_with("WHERE_IS_IT_TEST_JDK_").then(|| OsString::from(&home));
+        let from_env = env_jvms(&env_vars, &version_keys, lookup);
+
+        let os = test_os();
+        let cfg = Config {
+            paths: vec![dir.to_str().unwrap().to_string()],
+            env_vars,
+            version_keys,
+            sort: true,
+            vendor_priority: vec![],
+            follow_symlinks: false
+        };
+        let jvms: Vec<Jvm> = collate_with_env_jvms(&os, &cfg, from_env.clone())
+            .unwrap()
+            .into_iter()
+            .filter(|jvm| jvm.path.starts_with(dir.to_str().unwrap()))
+            .collect();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(from_env.len(), 1);
+        assert_eq!(from_env[0].version, "21.0.1");
+        assert_eq!(jvms.len(), 1);
+        assert_eq!(jvms[0].from_env, Some("WHERE_IS_IT_TEST_JDK_A".to_string()));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_elf_interpreter() {
+        // The test binary itself is a dynamically linked ELF executable
+        let exe = fs::read(std::env::current_exe().unwrap()).unwrap();
+        let interpreter = elf_interpreter(&exe).unwrap();
+        assert!(interpreter.contains("ld-linux") || interpreter.contains("ld-musl"));
+        // Only the launcher's header is read when scanning
+        assert_eq!(elf_interpreter(&exe[..exe.len().min(4096)]), Some(interpreter));
+        assert!(detect_libc(&exe[..exe.len().min(4096)]).is_some());
+        assert_eq!(elf_interpreter(b"#!/bin/sh\n"), None);
+
+        let mut musl = jvm("17");
+        musl.libc = Some("musl".to_string());
+        assert!(filter_libc(&Some("musl".to_string()), &musl));
+        assert!(!filter_libc(&Some("glibc".to_string()), &musl));
+        assert!(filter_libc(&Some("glibc".to_string()), &jvm("17")));
+    }
+
+    #[test]
+    fn test_exclude_paths() {
+        let dir = std::env::temp_dir().join(format!("where-is-it-exclude-{}", std::process::id()));
+        for sub in ["old/jdk-11", "old/nested/jdk-17", "older/jdk-21"] {
+            fs::create_dir_all(dir.join(sub)).unwrap();
+        }
+        let home = |sub: &str| {
+            let mut jvm = jvm("17.0.8");
+            jvm.path = dir.join(sub).to_str().unwrap().to_string();
+            jvm
+        };
+        let exclude = vec![dir.join("old").to_str().unwrap().to_string()];
+        let (old, nested, older) = (
+            filter_exclude_paths(&exclude, &home("old/jdk-11")),
+            filter_exclude_paths(&exclude, &home("old/nested/jdk-17")),
+            filter_exclude_paths(&exclude, &home("older/jdk-21"))
+        );
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(!old);
+        assert!(!nested);
+        assert!(older);
+        assert!(filter_exclude_paths(&[], &jvm("17.0.8")));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_follow_symlinks() {
+        let dir = std::env::temp_dir().join(format!("where-is-it-symlinks-{}", std::process::id()));
+        for version in ["17.0.8", "21.0.1"] {
+            let home = dir.join("installs").join(format!("jdk-{}", version));
+            write_release(&home, &[("JAVA_VERSION", version), ("OS_ARCH", "x86_64")]);
         }
+        let jdks = dir.join("jdks");
+        fs::create_dir_all(&jdks).unwrap();
+        std::os::unix::fs::symlink(dir.join("installs/jdk-17.0.8"), jdks.join("temurin-17")).unwrap();
+        std::os::unix::fs::symlink(dir.join("installs/jdk-21.0.1"), jdks.join("temurin-21")).unwrap();
+        // Another link to the same home is only reported once
+        std::os::unix::fs::symlink(dir.join("installs/jdk-21.0.1"), jdks.join("latest")).unwrap();
+
+        let os = test_os();
+        let paths = vec![jdks.to_str().unwrap().to_string()];
+        let scan = |follow_symlinks: Option<bool>| -> Vec<String> {
+            let cfg = Config::from_options(&MatchOptions {
+                paths: paths.clone(),
+                env_vars: Some(vec![]),
+                follow_symlinks,
+                ..Default::default()
+            });
+            collate_jvms(&os, &cfg).unwrap().into_iter().map(|jvm| jvm.version).collect()
+        };
+        let (skipped, followed) = (scan(None), scan(Some(true)));
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(skipped.is_empty());
+        assert_eq!(followed, vec!["21.0.1", "17.0.8"]);
+    }
+
+    #[test]
+    fn test_vendor_priority() {
+        let vendor = |version: &str, vendor: &str| {
+            let mut jvm = jvm(version);
+            jvm.vendor = vendor.to_string();
+            jvm
+        };
+        let mut jvms = [
+            vendor("17.0.8", "Oracle Corporation"),
+            vendor("17.0.8", "Azul Systems, Inc."),
+            vendor("21.0.1", "Oracle Corporation"),
+            vendor("17.0.8", "Eclipse Adoptium")
+        ];
+        let priority = ["adoptium".to_string(), "azul".to_string()];
+        jvms.sort_by(|a, b| compare_boosting_architecture(a, b, "x86_64", &priority));
+
+        let vendors: Vec<&str> = jvms.iter().map(|jvm| jvm.vendor.as_str()).collect();
+        // A newer version still wins over the preferred vendors
+        assert_eq!(vendors, vec!["Oracle Corporation", "Eclipse Adoptium", "Azul Systems, Inc.", "Oracle Corporation"]);
+        assert_eq!(jvms[0].version, "21.0.1");
+
+        // The options keep their case, the comparisons get the lowercased list
+        let cfg = Config::from_options(&MatchOptions { vendor_priority: vec!["Adoptium".to_string()], ..Default::default() });
+        assert_eq!(cfg.vendor_priority, vec!["adoptium"]);
+    }
+
+    #[test]
+    fn test_name_regex() {
+        let named = |name: &str| {
+            let mut jvm = jvm("17.0.8");
+            jvm.name = name.to_string();
+            jvm
+        };
+        let regex = |pattern: &str| compile_name_regex(&Some(pattern.to_string())).unwrap();
+
+        // Windows names are "<implementor> - <version>", macOS ones the CFBundleName
+        assert!(filter_name_regex(&regex("^IBM - 17\\."), &named("IBM - 17.0.8")));
+        assert!(!filter_name_regex(&regex("^IBM - 11\\."), &named("IBM - 17.0.8")));
+        assert!(filter_name_regex(&regex("Temurin.*17"), &named("Eclipse Temurin 17")));
+        assert!(!filter_name_regex(&regex("Temurin.*17"), &named("Zulu 17")));
+        assert!(!filter_name_regex(&regex("Temurin("), &named("Eclipse Temurin 17")));
+        assert!(compile_name_regex(&None).is_none());
+
+        let options = MatchOptions {
+            name: Some("Zulu 17".to_string()),
+            name_regex: Some("Temurin".to_string()),
+            ..Default::default()
+        };
+        let name_regex = compile_name_regex(&options.name_regex);
+        assert!(filter_jvm(&options, &name_regex, &named("Eclipse Temurin 17")));
+        assert!(!filter_jvm(&options, &name_regex, &named("Zulu 17")));
+        // Without a pattern, the exact name is used
+        assert!(filter_jvm(&options, &None, &named("Zulu 17")));
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    #[test]
+    fn test_jenv_file_version() {
+        let dir = std::env::temp_dir().join(format!("where-is-it-jenv-{}", std::process::id()));
+        let project = dir.join("project");
+        let nested = project.join("src").join("main");
+        let jenv_root = dir.join(".jenv");
+        fs::create_dir_all(&nested).unwrap();
+        fs::create_dir_all(&jenv_root).unwrap();
+        fs::write(jenv_root.join("version"), "11.0\n").unwrap();
+
+        let global = get_jenv_file_version(Some(&nested), &jenv_root);
+        fs::write(project.join(".java-version"), "17.0\n").unwrap();
+        // The working directory's own file wins over the global `~/.jenv/version`
+        let own = get_jenv_file_version(Some(&project), &jenv_root);
+        let from_ancestor = get_jenv_file_version(Some(&nested), &jenv_root);
+        fs::write(nested.join(".java-version"), "21.0\n").unwrap();
+        let closest = get_jenv_file_version(Some(&nested), &jenv_root);
+        let outside = get_jenv_file_version(Some(&jenv_root), &jenv_root);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(global, Some("11.0".to_string()));
+        assert_eq!(own, Some("17.0".to_string()));
+        assert_eq!(from_ancestor, Some("17.0".to_string()));
+        assert_eq!(closest, Some("21.0".to_string()));
+        assert_eq!(outside, Some("11.0".to_string()));
+    }
+
+    #[cfg(feature = "glob")]
+    #[test]
+    fn test_expand_paths() {
+        let dir = std::env::temp_dir().join(format!("where-is-it-glob-{}", std::process::id()));
+        for name in ["jdk-17", "jdk-21", "jre-17"] {
+            fs::create_dir_all(dir.join(name)).unwrap();
+        }
+        fs::write(dir.join("jdk-notes.txt"), "").unwrap();
+        let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+
+        let mut expanded = expand_paths(&[path("jdk-*")]);
+        expanded.sort();
+        let plain = expand_paths(&[path("missing")]);
+        let invalid = expand_paths(&[path("jdk-[")]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        // Only directories match, and plain paths are kept even when missing
+        assert_eq!(expanded, vec![path("jdk-17"), path("jdk-21")]);
+        assert_eq!(plain, vec![path("missing")]);
+        assert!(invalid.is_empty());
+    }
+
+    #[test]
+    fn test_lts_only() {
+        let lts_only = Some(true);
+        assert!(filter_lts(&lts_only, &jvm("1.8.0_292")));
+        assert!(filter_lts(&lts_only, &jvm("21.0.1")));
+        assert!(!filter_lts(&lts_only, &jvm("22.0.1")));
+        assert!(!filter_lts(&lts_only, &jvm("")));
+        assert!(filter_lts(&None, &jvm("22.0.1")));
+    }
+
+    #[test]
+    fn test_reconcile() {
+        let dir = std::env::temp_dir().join(format!("where-is-it-reconcile-{}", std::process::id()));
+        let java = if cfg!(windows) { "java.exe" } else { "java" };
+        for name in ["jdk-17", "jdk-21", "java-8-openjdk-amd64"] {
+            fs::create_dir_all(dir.join(name).join("bin")).unwrap();
+            fs::write(dir.join(name).join("bin").join(java), "").unwrap();
+        }
+        write_release(&dir.join("jdk-17"), &[("JAVA_VERSION", "17.0.8")]);
+        write_release(&dir.join("jdk-21"), &[("JAVA_VERSION", "21.0.2")]);
+        let home = |name: &str, version: &str| {
+            let mut jvm = jvm(version);
+            jvm.name = name.to_string();
+            jvm.path = dir.join(name).to_str().unwrap().to_string();
+            jvm
+        };
+
+        // Without a rescan only the cached homes are checked: gone (11) and updated
+        // (21) ones are dropped, one without a release file stays while java does
+        let options = MatchOptions { env_vars: Some(vec![]), ..Default::default() };
+        let validated = reconcile(
+            vec![home("jdk-17", "17.0.8"), home("jdk-11", "11.0.20"), home("jdk-21", "21.0.1"), home("java-8-openjdk-amd64", "8")],
+            options,
+            false
+        );
+
+        // The cached 17 is kept as-is even though the scan read it differently
+        let mut rescanned = home("jdk-17", "17.0.8");
+        rescanned.vendor = "Eclipse Adoptium".to_string();
+        let jvms = reconcile_jvms(vec![home("jdk-17", "17.0.8")], vec![home("jdk-21", "21.0.2"), rescanned]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let names = |jvms: &[Jvm]| -> Vec<String> { jvms.iter().map(|jvm| jvm.name.clone()).collect() };
+        assert_eq!(names(&validated), vec!["jdk-17", "java-8-openjdk-amd64"]);
+        assert_eq!(names(&jvms), vec!["jdk-17", "jdk-21"]);
+        assert_eq!(jvms[0].vendor, "");
+    }
+
+    #[test]
+    fn test_normalize_version() {
+        assert_eq!(normalize_version("1.8.0_292"), "8.0.292");
+        assert_eq!(normalize_version("1.8.0_292-b10"), "8.0.292");
+        assert_eq!(normalize_version("8u292"), "8.0.292");
+        assert_eq!(normalize_version(" 17.0.8+7 "), "17.0.8");
+        assert_eq!(normalize_version("21-ea"), "21");
+        assert_eq!(normalize_version("1.8"), "8");
+    }
+
+    #[test]
+    fn test_bits() {
+        assert_eq!(arch_bits(&normalize_arch("amd64")), Some(64));
+        assert_eq!(arch_bits(&normalize_arch("i686")), Some(32));
+        assert_eq!(arch_bits(""), None);
+
+        let mut elf = b"\x7fELF\x01\x01\x01".to_vec();
+        assert_eq!(executable_bits(&elf), Some(32));
+        elf[4] = 2;
+        assert_eq!(executable_bits(&elf), Some(64));
+        let mut pe = vec![0u8; 0x48];
+        pe[0..2].copy_from_slice(b"MZ");
+        pe[0x3c] = 0x40;
+        pe[0x40..0x44].copy_from_slice(b"PE\0\0");
+        pe[0x44..0x46].copy_from_slice(&0x14cu16.to_le_bytes());
+        assert_eq!(executable_bits(&pe), Some(32));
+        assert_eq!(executable_bits(&[0xcf, 0xfa, 0xed, 0xfe]), Some(64));
+        assert_eq!(executable_bits(b"#!/bin/sh"), None);
+
+        // Without an architecture in the release file, the executable decides
+        let dir = std::env::temp_dir().join(format!("where-is-it-bits-{}", std::process::id()));
+        let java = if cfg!(windows) { "java.exe" } else { "java" };
+        fs::create_dir_all(dir.join("bin")).unwrap();
+        fs::write(dir.join("bin").join(java), &pe).unwrap();
+        let bits = jvm_bits("", &launcher_header(&dir));
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(bits, Some(32));
+
+        let mut x86 = jvm("17.0.8");
+        x86.bits = Some(32);
+        assert!(filter_bits(&Some(32), &x86));
+        assert!(!filter_bits(&Some(64), &x86));
+        assert!(filter_bits(&None, &x86));
+    }
+
+    #[test]
+    fn test_filter_arch_aliases() {
+        let mut arm = jvm("17.0.8");
+        arm.architecture = "arm64".to_string();
+        assert!(filter_arch(&Some("aarch64".to_string()), &arm));
+        assert!(filter_arch(&Some("ARM64".to_string()), &arm));
+        assert!(!filter_arch(&Some("x86_64".to_string()), &arm));
+
+        let mut amd = jvm("17.0.8");
+        amd.architecture = "amd64".to_string();
+        assert!(filter_arch(&Some("x86_64".to_string()), &amd));
+        assert!(filter_arch(&Some("x64".to_string()), &amd));
+
+        assert_eq!(normalize_arch("i686"), "x86");
+        assert_eq!(normalize_arch("aarch64"), "aarch64");
+        assert_eq!(normalize_arch("ppc64le"), "ppc64le");
+    }
+
+    #[test]
+    fn test_find_bundle_home() {
+        let dir = std::env::temp_dir().join(format!("where-is-it-bundles-{}", std::process::id()));
+        let layouts = [
+            ("standard.jdk", "Contents/Home"),
+            ("root.jdk", ""),
+            ("jre.jdk", "Contents/Home/jre"),
+            ("nested.jdk", "zulu-8.jdk")
+        ];
+        for (bundle, home) in layouts {
+            let bin = dir.join(bundle).join(home).join("bin");
+            fs::create_dir_all(&bin).unwrap();
+            fs::write(bin.join("java"), "").unwrap();
+        }
+        fs::create_dir_all(dir.join("empty.jdk/Contents/Home")).unwrap();
+
+        for (bundle, home) in layouts {
+            let expected = dir.join(bundle).join(home);
+            assert_eq!(find_bundle_home(&dir.join(bundle)), Some(expected.clone()));
+        }
+        assert_eq!(find_bundle_home(&dir.join("empty.jdk")), None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_major_version() {
+        assert_eq!(jvm("1.8.0_292").major_version(), Some(8));
+        assert_eq!(jvm("17.0.8").major_version(), Some(17));
+        assert_eq!(jvm("21").major_version(), Some(21));
+        assert_eq!(jvm("11.0.20+8").major_version(), Some(11));
+        assert_eq!(jvm("").major_version(), None);
+    }
+
+    #[test]
+    fn test_nix_store_name() {
+        let home = Path::new("/nix/store/0a1b2c3d-openjdk-17.0.8+7/lib/openjdk");
+        assert_eq!(nix_store_name(home), Some("openjdk-17.0.8+7".to_string()));
+        assert_eq!(nix_store_name(Path::new("/usr/lib/jvm/java-17")), None);
+    }
+
+    #[test]
+    fn test_class_version() {
+        assert_eq!(class_file_version(0), 45);
+        assert_eq!(class_file_version(1), 45);
+        assert_eq!(class_file_version(8), 52);
+        assert_eq!(class_file_version(11), 55);
+        assert_eq!(class_file_version(17), 61);
+        assert_eq!(class_file_version(21), 65);
+
+        assert_eq!(jvm("1.8.0_292").class_version(), Some(52));
+        assert_eq!(jvm("17.0.8").class_version(), Some(61));
+
+        let dir = std::env::temp_dir().join(format!("where-is-it-class-{}", std::process::id()));
+        write_release(&dir, &[("JAVA_VERSION", "21.0.1")]);
+        let mut unknown = jvm("");
+        unknown.path = dir.to_str().unwrap().to_string();
+        assert_eq!(unknown.class_version(), Some(65));
+        assert_eq!(jvm("").class_version(), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_uname() {
+        assert_eq!(parse_uname("Linux x86_64\n"), Some(("Linux", "x86_64")));
+        assert_eq!(parse_uname("Darwin arm\r\n"), Some(("Darwin", "arm")));
+        assert_eq!(parse_uname("Linux unknown"), Some(("Linux", "unknown")));
+        assert_eq!(parse_uname("Linux\n"), None);
+        assert_eq!(parse_uname(""), None);
+    }
+
+    #[test]
+    fn test_satisfies() {
+        let jvm = jvm("17.0.8");
+        assert!(jvm.satisfies("17"));
+        assert!(jvm.satisfies("11+"));
+        assert!(jvm.satisfies("8, 17"));
+        assert!(jvm.satisfies(""));
+        assert!(!jvm.satisfies("21+"));
+        assert!(!jvm.satisfies("11,21"));
+    }
+
+    #[test]
+    fn test_parse_spec() {
+        let options = MatchOptions::parse("temurin@17");
+        assert_eq!(options.vendor.as_deref(), Some("temurin"));
+        assert_eq!(options.version.as_deref(), Some("17"));
+        assert_eq!(options.name, None);
+
+        let options = MatchOptions::parse("11+");
+        assert_eq!(options.vendor, None);
+        assert_eq!(options.version.as_deref(), Some("11+"));
+
+        let options = MatchOptions::parse("java-17-openjdk-amd64");
+        assert_eq!(options.name.as_deref(), Some("java-17-openjdk-amd64"));
+        assert_eq!(options.version, None);
     }
-    return true;
 }
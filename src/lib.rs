@@ -4,6 +4,17 @@ pub mod java;
 #[cfg(feature = "python")]
 pub mod python;
 
+#[cfg(feature = "config")]
+pub mod config;
+
+#[cfg(all(windows, any(feature = "java", feature = "python")))]
+mod scoop;
+
+#[cfg(feature = "serde")]
+mod summary;
+#[cfg(feature = "serde")]
+pub use summary::{discover_all, DiscoverySummary};
+
 
 // =================================
 
@@ -13,6 +24,7 @@ use napi_derive::napi;
 
 #[napi]
 #[cfg(feature = "node-compile")]
+#[allow(clippy::too_many_arguments)]
 pub fn node_find_python(
     major: Option<u32>,
     minor: Option<u32>,
@@ -20,7 +32,10 @@ pub fn node_find_python(
     pre: Option<bool>,
     dev: Option<bool>,
     name: Option<String>,
-    architecture: Option<String>
+    architecture: Option<String>,
+    venv: Option<bool>,
+    extra_paths: Option<Vec<String>>,
+    dedup_by_content: Option<bool>
 ) -> Vec<python::Version> {
     python::run(python::MatchOptions {
         major: match major {
@@ -38,16 +53,33 @@ pub fn node_find_python(
         pre,
         dev,
         name,
-        architecture
+        architecture,
+        venv,
+        extra_paths: extra_paths.unwrap_or_default(),
+        dedup_by_content,
+        ..Default::default()
     })
 }
 
 #[napi]
 #[cfg(feature = "node-compile")]
-pub fn node_find_java(name: Option<String>, arch: Option<String>, version: Option<String>) -> Vec<java::Jvm> {
+pub fn node_find_java(
+    name: Option<String>,
+    arch: Option<String>,
+    version: Option<String>,
+    vendor: Option<String>,
+    dedup_by_realpath: Option<bool>,
+    exclude_ea: Option<bool>,
+    paths: Option<Vec<String>>
+) -> Vec<java::Jvm> {
     java::run(java::MatchOptions {
         name,
+        vendor,
         arch,
-        version
+        version,
+        dedup_by_realpath,
+        exclude_ea,
+        paths: paths.unwrap_or_default(),
+        ..Default::default()
     })
 }
@@ -1,8 +1,19 @@
 // Heavily adapted from https://github.com/frostming/findpython
 
-use std::{collections::HashMap, io};
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+    path::PathBuf,
+    str::FromStr,
+    sync::{mpsc, Arc},
+    time::Duration,
+};
 
-use crate::python::{helpers::suffix_preference, providers::*, python::PythonVersion};
+use crate::python::{
+    helpers::suffix_preference,
+    providers::*,
+    python::{ProbeCacheScope, PythonVersion},
+};
 use fancy_regex::Regex;
 use lazy_static::lazy_static;
 
@@ -18,35 +29,184 @@ lazy_static! {
 }
 
 pub struct Finder {
-    providers: Vec<Box<dyn Provider>>,
+    providers: Vec<(String, Arc<dyn Provider>)>,
+    search_paths: Vec<PathBuf>,
     resolve_symlinks: bool,
     same_file: bool,
     same_interpreter: bool,
+    include_invalid: bool,
+    dedup: bool,
+    probe_timeout: Option<u64>,
+    probe_retries: Option<u32>,
+    provider_timeout: Option<Duration>,
+    max_per_provider: Option<usize>,
+    provider_priority: Vec<String>,
+    tiebreak: Tiebreak,
+    prefer_host_architecture: bool,
 }
 
+/// The bitness of the host, in the same form as `PythonVersion::architecture`
+fn host_architecture() -> &'static str {
+    // A 32-bit process on 64-bit Windows reports the real architecture here
+    let wow64 = cfg!(windows) && std::env::var_os("PROCESSOR_ARCHITEW6432").is_some();
+    if cfg!(target_pointer_width = "64") || wow64 {
+        "64bit"
+    } else {
+        "32bit"
+    }
+}
+
+/// How interpreters of equal version are ordered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Tiebreak {
+    /// The shortest executable path first, e.g. `/usr/bin` before `/usr/local/bin`
+    ShortestPath,
+    /// The longest executable path first
+    LongestPath,
+    /// In the order set by `Finder::with_provider_priority`, then the longest path first
+    #[default]
+    ProviderPriority,
+    /// The earliest in `PATH` first, as a shell would resolve them, then the longest
+    /// path first. Interpreters not found in `PATH` come last.
+    PathOrder,
+}
+
+/// Providers that don't read the home directory, kept by `Finder::with_fast_mode`
+static FAST_PROVIDERS: [&str; 2] = ["path", "winreg"];
+
+/// Comma separated provider names that `Finder::default` leaves out
+static DISABLE_PROVIDERS_ENV: &str = "WHERE_IS_IT_DISABLE_PROVIDERS";
+
 impl Default for Finder {
+    /// A finder using every available provider, except those listed in the
+    /// `WHERE_IS_IT_DISABLE_PROVIDERS` environment variable (e.g. `conda,asdf`).
     fn default() -> Self {
         let f = Self {
             providers: vec![],
+            search_paths: vec![],
             resolve_symlinks: false,
             same_file: true,
             same_interpreter: true,
+            include_invalid: false,
+            dedup: true,
+            probe_timeout: None,
+            probe_retries: None,
+            provider_timeout: None,
+            max_per_provider: None,
+            provider_priority: vec![],
+            tiebreak: Tiebreak::default(),
+            prefer_host_architecture: true,
         };
-        f.select_providers(&ALL_PROVIDERS[..]).unwrap()
+        let disabled = std::env::var(DISABLE_PROVIDERS_ENV).unwrap_or_default();
+        let disabled: Vec<&str> = disabled.split(',').map(|n| n.trim()).collect();
+        let names: Vec<&str> = ALL_PROVIDERS
+            .iter()
+            .copied()
+            .filter(|n| !disabled.contains(n))
+            .collect();
+        f.select_providers(&names).unwrap()
     }
 }
 
 impl Finder {
+    /// Use exactly the given providers. Unlike `Finder::default`, this ignores
+    /// `WHERE_IS_IT_DISABLE_PROVIDERS`.
     pub fn select_providers(mut self, names: &[&str]) -> Result<Self, io::Error> {
-        self.providers = names.iter().filter_map(|n| get_provider(*n)).collect();
+        self.providers = names
+            .iter()
+            .filter_map(|n| Some((n.to_string(), Arc::from(get_provider(n)?))))
+            .collect();
         Ok(self)
     }
 
+    /// Add the given providers after those already selected, e.g. the optional
+    /// `prefix` provider searching container install prefixes (`/opt/python/*/bin`, ...),
+    /// or on Windows the `wsl` provider running the default `python3` of each WSL distro.
+    pub fn enable_providers(mut self, names: &[&str]) -> Self {
+        for name in names {
+            if self.providers.iter().any(|(n, _)| n == name) {
+                continue;
+            }
+            if let Some(provider) = get_provider(name) {
+                self.providers.push((name.to_string(), Arc::from(provider)));
+            }
+        }
+        self
+    }
+
+    /// Leave out the given providers, keeping the others already selected.
+    pub fn disable_providers(mut self, names: &[&str]) -> Self {
+        self.providers.retain(|(name, _)| !names.contains(&name.as_str()));
+        self
+    }
+
+    /// When true, only keep the providers that don't walk the home directory (`path`
+    /// and, on Windows, `winreg`), which can be slow on network-mounted homes. Unlike
+    /// `select_providers`, this keeps any providers already disabled out. False is a no-op.
+    pub fn with_fast_mode(self, fast_mode: bool) -> Self {
+        if !fast_mode {
+            return self;
+        }
+        let slow: Vec<&str> = ALL_PROVIDERS
+            .iter()
+            .copied()
+            .filter(|n| !FAST_PROVIDERS.contains(n))
+            .collect();
+        self.disable_providers(&slow)
+    }
+
+    /// Report every interpreter of each `PATH` directory, e.g. `python3.11` and
+    /// `python3.12` next to `python3` in `/usr/bin`. By default only the first one
+    /// found in a directory is, since most are links to the same interpreter.
+    /// False is a no-op, as is true when the `path` provider isn't selected.
+    pub fn with_deep_path_scan(mut self, deep: bool) -> Self {
+        if !deep {
+            return self;
+        }
+        for (name, provider) in self.providers.iter_mut() {
+            if name == "path" {
+                *provider = Arc::new(PathProvider::new().with_deep_scan(true));
+            }
+        }
+        self
+    }
+
+    /// Enable the `prefix` provider searching the given roots instead of the usual
+    /// container install prefixes. Roots may contain wildcards, e.g. `/opt/python/*/bin`.
+    #[cfg(not(windows))]
+    pub fn with_prefix_roots(mut self, roots: Vec<String>) -> Self {
+        let provider = Arc::new(PrefixScanProvider::new(roots));
+        match self.providers.iter_mut().find(|(name, _)| name == "prefix") {
+            Some((_, existing)) => *existing = provider,
+            None => self.providers.push(("prefix".to_string(), provider)),
+        }
+        self
+    }
+
+    /// Additionally search the given directory for Python interpreters, in the
+    /// same way as the directories listed in `PATH`.
+    pub fn add_search_path(mut self, path: PathBuf) -> Self {
+        self.search_paths.push(path);
+        self
+    }
+
+    /// Additionally consider the interpreters at these exact paths, probed, filtered
+    /// and deduplicated like those of the other providers. They are reported with
+    /// the `explicit` provider, and paths that aren't files are skipped.
+    pub fn add_explicit(mut self, executables: Vec<PathBuf>) -> Self {
+        let provider = ExplicitProvider::new(executables);
+        self.providers
+            .push(("explicit".to_string(), Arc::new(provider)));
+        self
+    }
+
     pub fn resolve_symlinks(mut self, resolve_symlinks: bool) -> Self {
         self.resolve_symlinks = resolve_symlinks;
         self
     }
 
+    /// When false, interpreters are deduplicated by the hash of their executable's
+    /// contents. Every candidate is read in full, which costs some time.
     pub fn same_file(mut self, same_file: bool) -> Self {
         self.same_file = same_file;
         self
@@ -57,39 +217,302 @@ impl Finder {
         self
     }
 
+    /// Also return interpreters whose version could not be probed, as long as they
+    /// pass the filters that don't depend on the version (name, architecture, ...).
+    /// These are sorted after every valid interpreter.
+    pub fn with_include_invalid(mut self, include_invalid: bool) -> Self {
+        self.include_invalid = include_invalid;
+        self
+    }
+
+    /// When false, every discovered candidate is returned (still sorted), e.g. the
+    /// same version found by both pyenv and `PATH`. Note that the same interpreter
+    /// may then be returned several times under aliased paths (symlinks, shims, ...).
+    pub fn with_dedup(mut self, dedup: bool) -> Self {
+        self.dedup = dedup;
+        self
+    }
+
+    /// Timeout in seconds for each probe of an interpreter (version, architecture,
+    /// ...). An interpreter that doesn't answer in time is treated as invalid.
+    pub fn with_probe_timeout(mut self, timeout: u64) -> Self {
+        self.probe_timeout = Some(timeout);
+        self
+    }
+
+    /// How many times a probe is retried when an interpreter fails to spawn
+    /// temporarily (e.g. `EAGAIN` on a busy machine), 2 by default.
+    pub fn with_probe_retries(mut self, retries: u32) -> Self {
+        self.probe_retries = Some(retries);
+        self
+    }
+
+    /// Give up on a provider whose search takes longer than `timeout`, e.g. when
+    /// listing a stuck network filesystem hangs. Each provider then runs on a worker
+    /// thread; an abandoned provider simply contributes nothing, and its thread is
+    /// left to finish (or hang) in the background.
+    pub fn with_provider_timeout(mut self, timeout: Duration) -> Self {
+        self.provider_timeout = Some(timeout);
+        self
+    }
+
+    /// Only consider the first `max` candidates of each provider (the extra search
+    /// paths counting as one), so no more than that are probed. This trades
+    /// completeness for speed on systems with e.g. hundreds of conda environments:
+    /// interpreters past the cap are never seen, and the results are only sorted
+    /// among those that were.
+    pub fn with_max_per_provider(mut self, max: usize) -> Self {
+        self.max_per_provider = Some(max);
+        self
+    }
+
+    /// Among interpreters of equal version, prefer those found by providers listed
+    /// earlier (e.g. `&["pyenv", "path"]`). Providers not listed rank after listed
+    /// ones, and interpreters from extra search paths are tagged `search_path`.
+    /// By default no priority is applied. The version always takes precedence.
+    pub fn with_provider_priority(mut self, names: &[&str]) -> Self {
+        self.provider_priority = names.iter().map(|n| n.to_string()).collect();
+        self
+    }
+
+    /// How to order interpreters sharing a version, `Tiebreak::ProviderPriority` by default.
+    pub fn with_tiebreak(mut self, tiebreak: Tiebreak) -> Self {
+        self.tiebreak = tiebreak;
+        self
+    }
+
+    /// Among interpreters of equal version, prefer those matching the bitness of the
+    /// host (e.g. 64-bit over 32-bit on Windows) before applying the tie-break. Only
+    /// already known architectures are used (from the registry or an architecture
+    /// filter), so no interpreter is run for this. Enabled by default.
+    pub fn with_prefer_host_architecture(mut self, prefer_host_architecture: bool) -> Self {
+        self.prefer_host_architecture = prefer_host_architecture;
+        self
+    }
+
+    // 0 for the host's bitness, 1 when unknown and 2 for any other
+    fn architecture_rank(&self, python: &PythonVersion) -> usize {
+        if !self.prefer_host_architecture {
+            return 0;
+        }
+        match python.known_architecture() {
+            Some(arch) if arch == host_architecture() => 0,
+            Some(_) => 2,
+            None => 1,
+        }
+    }
+
+    fn provider_rank(&self, python: &PythonVersion) -> usize {
+        self.provider_priority
+            .iter()
+            .position(|name| Some(name) == python.provider.as_ref())
+            .unwrap_or(self.provider_priority.len())
+    }
+
     fn find_all_python_versions(&self) -> Vec<PythonVersion> {
+        self.iter_python_versions().collect()
+    }
+
+    // Lazily walks the providers, so consumers can stop early
+    fn iter_python_versions(&self) -> impl Iterator<Item = PythonVersion> + '_ {
         self.providers
             .iter()
-            .flat_map(|p| p.find_pythons())
-            .collect()
+            .flat_map(|(name, p)| {
+                #[cfg(feature = "tracing")]
+                let _span = tracing::info_span!("find_pythons", provider = name.as_str()).entered();
+                #[cfg(feature = "tracing")]
+                let start = std::time::Instant::now();
+
+                let found = self.run_provider(p);
+
+                #[cfg(feature = "tracing")]
+                tracing::debug!(count = found.len(), elapsed = ?start.elapsed(), "provider finished");
+
+                found
+                    .into_iter()
+                    .take(self.max_per_provider.unwrap_or(usize::MAX))
+                    .map(|python| python.with_provider(name))
+            })
+            .chain(
+                self.search_paths
+                    .iter()
+                    .flat_map(|path| find_pythons_from_path(path, false))
+                    .take(self.max_per_provider.unwrap_or(usize::MAX))
+                    .map(|python| python.with_provider("search_path")),
+            )
+            .map(|python| match self.probe_timeout {
+                Some(timeout) => python.with_timeout(timeout),
+                None => python,
+            })
+            .map(|python| match self.probe_retries {
+                Some(retries) => python.with_retries(retries),
+                None => python,
+            })
+    }
+
+    fn run_provider(&self, provider: &Arc<dyn Provider>) -> Vec<PythonVersion> {
+        let timeout = match self.provider_timeout {
+            Some(timeout) => timeout,
+            None => return provider.find_pythons(),
+        };
+        let provider = Arc::clone(provider);
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            // Fails when the provider was already abandoned
+            let _ = sender.send(provider.find_pythons());
+        });
+        receiver.recv_timeout(timeout).unwrap_or_default()
+    }
+
+    fn accepts(&self, python: &PythonVersion, options: &MatchOptions) -> bool {
+        if !options.probe_versions.unwrap_or(true) {
+            return python.matches_unversioned(options);
+        }
+        python.matches(options)
+            || (self.include_invalid && !python.is_valid() && python.matches_unversioned(options))
     }
 
     pub fn find_all(&self, options: MatchOptions) -> Vec<PythonVersion> {
+        let _probe_cache = ProbeCacheScope::enter();
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("find_all").entered();
+        #[cfg(feature = "tracing")]
+        let start = std::time::Instant::now();
+
         let pythons = self.find_all_python_versions();
+        #[cfg(feature = "tracing")]
+        let candidates = pythons.len();
         let mut filtered = vec![];
         for python in pythons {
-            if python.matches(&options) {
+            if self.accepts(&python, &options) {
                 filtered.push(python);
             }
         }
-        self.deduplicate(filtered)
+        let result = self.collect_results(filtered, &options);
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            candidates,
+            found = result.len(),
+            elapsed = ?start.elapsed(),
+            "python discovery finished"
+        );
+
+        result
+    }
+
+    /// Run several queries against a single discovery pass. Each interpreter is
+    /// probed at most once and the results of every query are deduplicated separately.
+    pub fn find_batch(&self, options: &[MatchOptions]) -> Vec<Vec<PythonVersion>> {
+        let _probe_cache = ProbeCacheScope::enter();
+        let pythons = self.find_all_python_versions();
+        options
+            .iter()
+            .map(|options| {
+                let filtered = pythons
+                    .iter()
+                    .filter(|python| self.accepts(python, options))
+                    .cloned()
+                    .collect();
+                self.collect_results(filtered, options)
+            })
+            .collect()
+    }
+
+    /// The interpreters of `pythons` matching the options, deduplicated and sorted
+    /// like the results of `find_all`
+    pub(crate) fn select(
+        &self,
+        pythons: Vec<PythonVersion>,
+        options: MatchOptions,
+    ) -> Vec<PythonVersion> {
+        let _probe_cache = ProbeCacheScope::enter();
+        let filtered = pythons
+            .into_iter()
+            .filter(|python| self.accepts(python, &options))
+            .collect();
+        self.collect_results(filtered, &options)
+    }
+
+    /// Whether any interpreter matches. Stops at the first match, skipping the
+    /// remaining providers and deduplication.
+    pub fn any(&self, options: MatchOptions) -> bool {
+        let _probe_cache = ProbeCacheScope::enter();
+        self.iter_python_versions()
+            .any(|python| self.accepts(&python, &options))
+    }
+
+    /// Lazily yields the matching interpreters as the providers find them, so a
+    /// consumer can start on the first results before every provider has run.
+    /// Interpreters are yielded in discovery order: unlike `find_all`, they are
+    /// not sorted, and duplicates are dropped on first sight rather than keeping
+    /// the preferred path. Interpreters sharing an executable are probed for each path.
+    pub fn iter(&self, options: MatchOptions) -> impl Iterator<Item = PythonVersion> + '_ {
+        let dedup = self.dedup && options.dedup.unwrap_or(true);
+        let mut seen = HashSet::new();
+        self.iter_python_versions().filter_map(move |mut python| {
+            if !self.accepts(&python, &options) {
+                return None;
+            }
+            if dedup && !seen.insert(self.deduplicate_key(&mut python)) {
+                return None;
+            }
+            Some(python)
+        })
     }
 
     pub fn find(&self, options: MatchOptions) -> Option<PythonVersion> {
         self.find_all(options).first().cloned()
     }
 
+    /// The newest interpreter whose version doesn't exceed `ceiling`, e.g. the newest
+    /// 3.11 or older for `3.11`. The version is compared up to the precision of the
+    /// ceiling, so `3.11` allows every 3.11.x while `3.11.4` allows up to 3.11.4.
+    /// Returns `None` when nothing qualifies or the ceiling isn't a valid version.
+    pub fn find_highest_below(&self, ceiling: &str) -> Option<PythonVersion> {
+        let ceiling = pep440_rs::Version::from_str(ceiling).ok()?;
+        self.find_all(MatchOptions::default())
+            .into_iter()
+            .find(|python| match python.version() {
+                Ok(version) => {
+                    let len = version.release.len().min(ceiling.release.len());
+                    version.release[..len] <= ceiling.release[..]
+                        && (version.release.len() > ceiling.release.len() || version <= ceiling)
+                }
+                Err(_) => false,
+            })
+    }
+
     fn deduplicate_key(&self, python: &mut PythonVersion) -> String {
         if !self.same_interpreter {
-            return python.interpreter().unwrap().to_str().unwrap().to_string();
+            if let Ok(interpreter) = python.interpreter() {
+                return interpreter.to_string_lossy().to_string();
+            }
         }
         if !self.same_file {
-            return python.content_hash().unwrap();
+            if let Ok(hash) = python.content_hash() {
+                return hash;
+            }
         }
         if self.resolve_symlinks && !python.keep_symlink {
-            return python.real_path().to_str().unwrap().to_string();
+            return python.real_path().to_string_lossy().to_string();
         }
-        python.executable.to_str().unwrap().to_string()
+        python.executable.to_string_lossy().to_string()
+    }
+
+    fn collect_results(&self, versions: Vec<PythonVersion>, options: &MatchOptions) -> Vec<PythonVersion> {
+        if !options.probe_versions.unwrap_or(true) {
+            return self.deduplicate_unprobed(versions, options);
+        }
+        let mut result = if self.dedup && options.dedup.unwrap_or(true) {
+            self.deduplicate(versions)
+        } else {
+            self.sort(versions)
+        };
+        if options.highest_only.unwrap_or(false) {
+            result.truncate(1);
+        }
+        result
     }
 
     fn deduplicate(&self, versions: Vec<PythonVersion>) -> Vec<PythonVersion> {
@@ -101,31 +524,167 @@ impl Finder {
                 p.executable.is_symlink(),
                 suffix_preference(&p.executable),
                 -(p.executable.to_string_lossy().len() as isize),
+                self.provider_rank(p),
             )
         });
 
         for version in versions.iter_mut() {
             let key = self.deduplicate_key(version);
-            result.entry(key).or_insert(version.to_owned());
+            // Keep the pyenv selection and the PATH position when it's found under
+            // another path too
+            result
+                .entry(key)
+                .and_modify(|kept: &mut PythonVersion| {
+                    kept.is_default |= version.is_default;
+                    kept.path_index = match (kept.path_index, version.path_index) {
+                        (Some(a), Some(b)) => Some(a.min(b)),
+                        (a, b) => a.or(b),
+                    };
+                })
+                .or_insert(version.to_owned());
         }
-        let mut py_versions = result.into_values().collect::<Vec<_>>();
+        self.sort(result.into_values().collect())
+    }
+
+    // Keeps discovery order, and only compares paths as the rest needs a probe
+    fn deduplicate_unprobed(
+        &self,
+        versions: Vec<PythonVersion>,
+        options: &MatchOptions,
+    ) -> Vec<PythonVersion> {
+        if !(self.dedup && options.dedup.unwrap_or(true)) {
+            return versions;
+        }
+        let mut seen = HashSet::new();
+        versions
+            .into_iter()
+            .filter(|python| {
+                seen.insert(if self.resolve_symlinks && !python.keep_symlink {
+                    python.real_path()
+                } else {
+                    python.executable.clone()
+                })
+            })
+            .collect()
+    }
+
+    fn sort(&self, versions: Vec<PythonVersion>) -> Vec<PythonVersion> {
+        let mut py_versions = versions;
         py_versions.sort_by(|a, b| {
-            (b.version().unwrap(), b.executable.to_string_lossy().len())
-                .cmp(&(a.version().unwrap(), a.executable.to_string_lossy().len()))
+            let path_len = |p: &PythonVersion| p.executable.to_string_lossy().len();
+            b.version()
+                .ok()
+                .cmp(&a.version().ok())
+                .then_with(|| self.architecture_rank(a).cmp(&self.architecture_rank(b)))
+                .then_with(|| match self.tiebreak {
+                    Tiebreak::ShortestPath => path_len(a).cmp(&path_len(b)),
+                    Tiebreak::LongestPath => path_len(b).cmp(&path_len(a)),
+                    Tiebreak::ProviderPriority => self
+                        .provider_rank(a)
+                        .cmp(&self.provider_rank(b))
+                        .then_with(|| path_len(b).cmp(&path_len(a))),
+                    Tiebreak::PathOrder => {
+                        let path_index = |p: &PythonVersion| p.path_index.unwrap_or(usize::MAX);
+                        path_index(a)
+                            .cmp(&path_index(b))
+                            .then_with(|| path_len(b).cmp(&path_len(a)))
+                    }
+                })
         });
         py_versions
     }
 }
 
+/// The kind of a prerelease, as in `3.13.0a1`, `3.13.0b2` or `3.13.0rc1`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrereleaseKind {
+    Alpha,
+    Beta,
+    Rc,
+}
+
+impl From<pep440_rs::PreRelease> for PrereleaseKind {
+    fn from(pre: pep440_rs::PreRelease) -> Self {
+        match pre {
+            pep440_rs::PreRelease::Alpha => PrereleaseKind::Alpha,
+            pep440_rs::PreRelease::Beta => PrereleaseKind::Beta,
+            pep440_rs::PreRelease::Rc => PrereleaseKind::Rc,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct MatchOptions {
     pub major: Option<usize>,
     pub minor: Option<usize>,
     pub patch: Option<usize>,
     pub pre: Option<bool>,
+    /// Only match prereleases of this kind, e.g. release candidates
+    pub prerelease: Option<PrereleaseKind>,
     pub dev: Option<bool>,
+    /// The executable's file name, e.g. `python3`. An `.exe` suffix is ignored on both
+    /// sides, so `python3` matches `python3.exe`. The name is only compared, never used
+    /// to infer a version: the version filters always apply to the probed version, so
+    /// `python3.11` with `minor = 12` matches nothing.
     pub name: Option<String>,
+    /// `Some(true)` to match any executable whose name starts with `name`, e.g.
+    /// `python3` matching `python3.11`
+    pub name_is_prefix: Option<bool>,
+    /// Either a bitness (`64bit`, `32bit`) or a machine type (`x86_64`, `x86`,
+    /// `aarch64`, `arm`). Aliases such as `amd64`, `x64`, `i686` and `arm64` are accepted.
     pub architecture: Option<String>,
+    /// Lowest version to match, inclusive (e.g. `3.10.6`). Combine with `major` and
+    /// `minor` to set a patch floor within a minor series.
+    pub min_version: Option<String>,
+    /// Highest version to match, inclusive
+    pub max_version: Option<String>,
+    /// Only match standalone builds linked against this C library (`glibc` or `musl`).
+    /// Interpreters whose C library can't be inferred are kept, see `PythonVersion::libc`
+    pub libc: Option<String>,
+    /// `Some(true)` to only match virtual environments, `Some(false)` to exclude them
+    pub venv: Option<bool>,
+    /// Extra directories to search in addition to the providers
+    pub extra_paths: Vec<String>,
+    /// Drop interpreters whose canonical path is inside one of these directories.
+    /// Whole path components are compared, so `/opt/old` doesn't exclude `/opt/older`
+    pub exclude_paths: Vec<String>,
+    /// Collapse interpreters with byte-identical executables (e.g. hardlinked copies).
+    /// This reads and hashes every matching executable, so it is noticeably slower.
+    pub dedup_by_content: Option<bool>,
+    /// `Some(false)` to return every candidate instead of deduplicating, see `Finder::with_dedup`
+    pub dedup: Option<bool>,
+    /// How to order interpreters sharing a version, see `Finder::with_tiebreak`
+    pub tiebreak: Option<Tiebreak>,
+    /// Skip the providers walking the home directory, see `Finder::with_fast_mode`
+    pub fast_mode: Option<bool>,
+    /// Report every interpreter of each `PATH` directory, see `Finder::with_deep_path_scan`
+    pub deep_path_scan: Option<bool>,
+    /// Consider at most this many candidates per provider, see `Finder::with_max_per_provider`
+    pub max_per_provider: Option<usize>,
+    /// `Some(true)` to only match interpreters that can import pip, see `PythonVersion::has_pip`
+    pub require_pip: Option<bool>,
+    /// `Some(true)` to only match interpreters that can import tkinter, see
+    /// `PythonVersion::has_tkinter`
+    pub require_tkinter: Option<bool>,
+    /// `Some(true)` to only match interpreters with a usable `ssl` module, see
+    /// `PythonVersion::has_ssl`
+    pub require_ssl: Option<bool>,
+    /// `Some(true)` to only match interpreters with their development headers and
+    /// config script installed, see `PythonVersion::has_dev_tools`
+    pub require_dev: Option<bool>,
+    /// `Some(true)` to only return the highest matching version, e.g. the newest 3.x
+    /// for `major = 3`, like [`Finder::find`] but for `Finder::find_all` and `run`.
+    pub highest_only: Option<bool>,
+    /// `Some(false)` to list the interpreters without running them, for a near-instant
+    /// listing. Only the name, path, venv, libc and architecture filters apply, the
+    /// latter still running interpreters whose architecture isn't otherwise known.
+    /// Version-based filters, `require_*` and `highest_only` are ignored, and the
+    /// results are in discovery order, deduplicated by path.
+    pub probe_versions: Option<bool>,
+    /// `Some(true)` to also report the environment markers, SOABI tag and
+    /// site-packages directory of each result, see `PythonVersion::markers`. This runs every returned interpreter
+    /// once more, so they are left out by default.
+    pub probe_details: Option<bool>,
 }
 
 impl MatchOptions {
@@ -136,22 +695,34 @@ impl MatchOptions {
                 minor: capture.name("minor").map(|m| m.as_str().parse().unwrap()),
                 patch: capture.name("patch").map(|m| m.as_str().parse().unwrap()),
                 pre: capture.name("prerel").map(|_| true),
+                prerelease: capture.name("prerel").and_then(|m| match m.as_str() {
+                    "a" => Some(PrereleaseKind::Alpha),
+                    "b" => Some(PrereleaseKind::Beta),
+                    "c" | "rc" => Some(PrereleaseKind::Rc),
+                    _ => None,
+                }),
                 dev: capture.name("dev").map(|_| true),
                 name: None,
                 architecture: capture
                     .name("architecture")
                     .map(|m| format!("{}bit", m.as_str())),
+                ..Default::default()
             }),
             _ => None,
         }
     }
 
+    /// Set the filters from a `[name@]version` spec such as `3.11`, `3.12-64` or
+    /// `python3@3.12`. A spec whose version part can't be parsed is used as a name.
     pub fn version_spec(self, version: &str) -> Self {
-        if let Some(res) = Self::from_version(version) {
-            res
-        } else {
-            self.name(version)
+        if let Some((name, spec)) = version.split_once('@') {
+            if let Some(res) = Self::from_version(spec) {
+                return if name.is_empty() { res } else { res.name(name) };
+            }
+        } else if let Some(res) = Self::from_version(version) {
+            return res;
         }
+        self.name(version)
     }
 
     pub fn major(mut self, major: usize) -> Self {
@@ -174,6 +745,11 @@ impl MatchOptions {
         self
     }
 
+    pub fn prerelease(mut self, prerelease: PrereleaseKind) -> Self {
+        self.prerelease = Some(prerelease);
+        self
+    }
+
     pub fn dev(mut self, dev: bool) -> Self {
         self.dev = Some(dev);
         self
@@ -184,8 +760,722 @@ impl MatchOptions {
         self
     }
 
+    pub fn name_is_prefix(mut self, name_is_prefix: bool) -> Self {
+        self.name_is_prefix = Some(name_is_prefix);
+        self
+    }
+
     pub fn architecture(mut self, architecture: &str) -> Self {
         self.architecture = Some(architecture.to_string());
         self
     }
+
+    pub fn min_version(mut self, min_version: &str) -> Self {
+        self.min_version = Some(min_version.to_string());
+        self
+    }
+
+    pub fn max_version(mut self, max_version: &str) -> Self {
+        self.max_version = Some(max_version.to_string());
+        self
+    }
+
+    pub fn libc(mut self, libc: &str) -> Self {
+        self.libc = Some(libc.to_string());
+        self
+    }
+
+    pub fn venv(mut self, venv: bool) -> Self {
+        self.venv = Some(venv);
+        self
+    }
+
+    pub fn extra_path(mut self, path: &str) -> Self {
+        self.extra_paths.push(path.to_string());
+        self
+    }
+
+    pub fn exclude_path(mut self, path: &str) -> Self {
+        self.exclude_paths.push(path.to_string());
+        self
+    }
+
+    pub fn dedup_by_content(mut self, dedup_by_content: bool) -> Self {
+        self.dedup_by_content = Some(dedup_by_content);
+        self
+    }
+
+    pub fn dedup(mut self, dedup: bool) -> Self {
+        self.dedup = Some(dedup);
+        self
+    }
+
+    pub fn tiebreak(mut self, tiebreak: Tiebreak) -> Self {
+        self.tiebreak = Some(tiebreak);
+        self
+    }
+
+    pub fn fast_mode(mut self, fast_mode: bool) -> Self {
+        self.fast_mode = Some(fast_mode);
+        self
+    }
+
+    pub fn deep_path_scan(mut self, deep_path_scan: bool) -> Self {
+        self.deep_path_scan = Some(deep_path_scan);
+        self
+    }
+
+    pub fn max_per_provider(mut self, max: usize) -> Self {
+        self.max_per_provider = Some(max);
+        self
+    }
+
+    pub fn require_pip(mut self, require_pip: bool) -> Self {
+        self.require_pip = Some(require_pip);
+        self
+    }
+
+    pub fn require_tkinter(mut self, require_tkinter: bool) -> Self {
+        self.require_tkinter = Some(require_tkinter);
+        self
+    }
+
+    pub fn require_ssl(mut self, require_ssl: bool) -> Self {
+        self.require_ssl = Some(require_ssl);
+        self
+    }
+
+    pub fn require_dev(mut self, require_dev: bool) -> Self {
+        self.require_dev = Some(require_dev);
+        self
+    }
+
+    pub fn highest_only(mut self, highest_only: bool) -> Self {
+        self.highest_only = Some(highest_only);
+        self
+    }
+
+    pub fn probe_versions(mut self, probe_versions: bool) -> Self {
+        self.probe_versions = Some(probe_versions);
+        self
+    }
+
+    pub fn probe_details(mut self, probe_details: bool) -> Self {
+        self.probe_details = Some(probe_details);
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::python::testing::TempDir;
+    #[cfg(unix)]
+    use crate::python::testing::{fake_python, fake_script};
+    use std::str::FromStr;
+
+    fn python(version: &str) -> PythonVersion {
+        PythonVersion::new(PathBuf::from("python3"))
+            .with_version(pep440_rs::Version::from_str(version).unwrap())
+    }
+
+    #[test]
+    fn test_version_spec_patch() {
+        let any_patch = MatchOptions::default().version_spec("3.11");
+        assert_eq!(any_patch.patch, None);
+        assert!(python("3.11.4").matches(&any_patch));
+        assert!(python("3.11.0").matches(&any_patch));
+        assert!(!python("3.12.0").matches(&any_patch));
+
+        let pinned = MatchOptions::default().version_spec("3.11.0");
+        assert_eq!(pinned.patch, Some(0));
+        assert!(!python("3.11.4").matches(&pinned));
+        assert!(python("3.11.0").matches(&pinned));
+    }
+
+    #[test]
+    fn test_name() {
+        let named = |path: &str| PythonVersion::new(PathBuf::from(path));
+        let exact = MatchOptions::default().name("python3");
+        assert!(named("bin/python3").matches_unversioned(&exact));
+        assert!(named("Scripts/python3.exe").matches_unversioned(&exact));
+        assert!(named("Scripts/python3.EXE").matches_unversioned(&exact));
+        assert!(!named("bin/python3.11").matches_unversioned(&exact));
+        assert!(!named("bin/python").matches_unversioned(&exact));
+        assert!(
+            named("python3.exe").matches_unversioned(&MatchOptions::default().name("python3.exe"))
+        );
+
+        let prefix = MatchOptions::default().name("python3").name_is_prefix(true);
+        assert!(named("bin/python3").matches_unversioned(&prefix));
+        assert!(named("bin/python3.11").matches_unversioned(&prefix));
+        assert!(named("Scripts/python3.12.exe").matches_unversioned(&prefix));
+        assert!(!named("bin/python").matches_unversioned(&prefix));
+        assert!(!named("bin/pypy3").matches_unversioned(&prefix));
+    }
+
+    #[test]
+    fn test_exclude_paths() {
+        let dir = TempDir::new("exclude-py");
+        for sub in ["old", "old/nested", "older"] {
+            std::fs::create_dir_all(dir.join(sub)).unwrap();
+            std::fs::write(dir.join(sub).join("python3"), "").unwrap();
+        }
+        let python = |sub: &str| PythonVersion::new(dir.join(sub).join("python3"));
+        let options = MatchOptions::default().exclude_path(dir.join("old").to_str().unwrap());
+        let (old, nested, older) = (
+            python("old").matches_unversioned(&options),
+            python("old/nested").matches_unversioned(&options),
+            python("older").matches_unversioned(&options),
+        );
+
+        assert!(!old);
+        assert!(!nested);
+        assert!(older);
+    }
+
+    #[test]
+    fn test_prerelease_kind() {
+        let rc = MatchOptions::default().prerelease(PrereleaseKind::Rc);
+        assert!(python("3.13.0rc1").matches(&rc));
+        assert!(!python("3.13.0a1").matches(&rc));
+        assert!(!python("3.13.0b2").matches(&rc));
+        assert!(!python("3.12.4").matches(&rc));
+
+        let spec = MatchOptions::default().version_spec("3.13rc");
+        assert_eq!(spec.prerelease, Some(PrereleaseKind::Rc));
+        assert!(python("3.13.0rc2").matches(&spec));
+        assert!(!python("3.13.0b1").matches(&spec));
+
+        let any_pre = MatchOptions::default().pre(true);
+        assert!(python("3.13.0a1").matches(&any_pre));
+        assert!(!python("3.12.4").matches(&any_pre));
+    }
+
+    #[test]
+    fn test_patch_floor_within_minor() {
+        let options = MatchOptions::default().major(3).minor(10).min_version("3.10.6");
+        assert!(python("3.10.6").matches(&options));
+        assert!(python("3.10.12").matches(&options));
+        assert!(!python("3.10.4").matches(&options));
+        assert!(!python("3.11.0").matches(&options));
+
+        // Without the minor, the range alone lets later series through
+        let range = MatchOptions::default().min_version("3.10.6").max_version("3.11.2");
+        assert!(python("3.11.0").matches(&range));
+        assert!(!python("3.11.3").matches(&range));
+        assert!(!python("3.10.5").matches(&range));
+
+        let invalid = MatchOptions::default().min_version("three");
+        assert!(!python("3.10.6").matches(&invalid));
+    }
+
+    #[test]
+    fn test_fast_mode() {
+        let finder = Finder::default()
+            .select_providers(&["path", "conda", "pyenv"])
+            .unwrap()
+            .with_fast_mode(true);
+        let names: Vec<&str> = finder.providers.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, vec!["path"]);
+    }
+
+    #[test]
+    fn test_tiebreak() {
+        let candidates = || {
+            vec![
+                PythonVersion::new(PathBuf::from("/usr/bin/python3"))
+                    .with_version(pep440_rs::Version::from_str("3.11.4").unwrap())
+                    .with_provider("path"),
+                PythonVersion::new(PathBuf::from("/usr/local/bin/python3"))
+                    .with_version(pep440_rs::Version::from_str("3.11.4").unwrap())
+                    .with_provider("pyenv"),
+            ]
+        };
+        let first = |finder: Finder| finder.sort(candidates())[0].executable.clone();
+        let finder = || Finder::default().select_providers(&[]).unwrap();
+
+        assert_eq!(first(finder()), PathBuf::from("/usr/local/bin/python3"));
+        assert_eq!(
+            first(finder().with_tiebreak(Tiebreak::ShortestPath)),
+            PathBuf::from("/usr/bin/python3")
+        );
+        assert_eq!(
+            first(finder().with_tiebreak(Tiebreak::LongestPath)),
+            PathBuf::from("/usr/local/bin/python3")
+        );
+        assert_eq!(
+            first(finder().with_provider_priority(&["path"])),
+            PathBuf::from("/usr/bin/python3")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_add_explicit() {
+        let dir = TempDir::new("explicit");
+        for version in ["3.11.2", "3.12.1"] {
+            fake_python(&dir, &format!("build-{}", version), version);
+        }
+        let finder = Finder::default()
+            .select_providers(&[])
+            .unwrap()
+            .add_explicit(vec![
+                dir.join("build-3.11.2"),
+                dir.join("missing"),
+                dir.join("build-3.12.1"),
+            ]);
+
+        let found: Vec<(String, Option<String>)> = finder
+            .find_all(MatchOptions::default().version_spec("3"))
+            .iter()
+            .map(|p| (p.version().unwrap().to_string(), p.provider.clone()))
+            .collect();
+
+        let explicit = Some("explicit".to_string());
+        assert_eq!(
+            found,
+            vec![
+                ("3.12.1".to_string(), explicit.clone()),
+                ("3.11.2".to_string(), explicit)
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_with_prefix_roots() {
+        let dir = TempDir::new("prefix-roots");
+        for (build, version) in [("cp311", "3.11.4"), ("cp312", "3.12.1")] {
+            fake_python(&dir, &format!("{}/bin/python3", build), version);
+        }
+        let root = dir.join("*").join("bin").to_str().unwrap().to_string();
+        let finder = Finder::default()
+            .select_providers(&[])
+            .unwrap()
+            .with_prefix_roots(vec![root]);
+
+        let found: Vec<(String, Option<String>)> = finder
+            .find_all(MatchOptions::default().version_spec("3"))
+            .iter()
+            .map(|p| (p.version().unwrap().to_string(), p.provider.clone()))
+            .collect();
+
+        let prefix = Some("prefix".to_string());
+        assert_eq!(
+            found,
+            vec![
+                ("3.12.1".to_string(), prefix.clone()),
+                ("3.11.4".to_string(), prefix)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_path_order() {
+        let python = |path: &str| {
+            PythonVersion::new(PathBuf::from(path))
+                .with_version(pep440_rs::Version::from_str("3.11.4").unwrap())
+        };
+        let finder = Finder::default()
+            .select_providers(&[])
+            .unwrap()
+            .with_tiebreak(Tiebreak::PathOrder);
+        let sorted = finder.sort(vec![
+            python("/opt/python/bin/python3"),
+            python("/usr/local/bin/python3").with_path_index(2),
+            python("/usr/bin/python3").with_path_index(0),
+        ]);
+        let paths: Vec<&str> = sorted
+            .iter()
+            .map(|p| p.executable.to_str().unwrap())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "/usr/bin/python3",
+                "/usr/local/bin/python3",
+                "/opt/python/bin/python3"
+            ]
+        );
+
+        // A duplicate keeps the earliest PATH position of its copies
+        let deduplicated = finder.deduplicate(vec![
+            python("/usr/local/bin/python3").with_path_index(3),
+            python("/usr/local/bin/python3").with_path_index(1),
+        ]);
+        assert_eq!(deduplicated.len(), 1);
+        assert_eq!(deduplicated[0].path_index, Some(1));
+    }
+
+    #[test]
+    fn test_prefer_host_architecture() {
+        let candidates = || {
+            vec![
+                PythonVersion::new(PathBuf::from("C:\\Python311-32\\python.exe"))
+                    .with_version(pep440_rs::Version::from_str("3.11.4").unwrap())
+                    .with_architecture(if host_architecture() == "64bit" {
+                        "32bit"
+                    } else {
+                        "64bit"
+                    }),
+                PythonVersion::new(PathBuf::from("C:\\Python311\\python.exe"))
+                    .with_version(pep440_rs::Version::from_str("3.11.4").unwrap())
+                    .with_architecture(host_architecture()),
+            ]
+        };
+        let finder = || Finder::default().select_providers(&[]).unwrap();
+
+        let preferred = finder().sort(candidates());
+        assert_eq!(
+            preferred[0].known_architecture().as_deref(),
+            Some(host_architecture())
+        );
+        let longest = finder()
+            .with_prefer_host_architecture(false)
+            .sort(candidates());
+        assert_ne!(
+            longest[0].known_architecture().as_deref(),
+            Some(host_architecture())
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_highest_below() {
+        let dir = TempDir::new("ceiling");
+        let mut finder = Finder::default().select_providers(&[]).unwrap();
+        for version in ["3.10.4", "3.11.2", "3.12.1"] {
+            fake_python(&dir, &format!("{}/python3", version), version);
+            finder = finder.add_search_path(dir.join(version));
+        }
+
+        let version =
+            |python: Option<PythonVersion>| python.map(|p| p.version().unwrap().to_string());
+        let (minor, patch, floor, invalid) = (
+            version(finder.find_highest_below("3.11")),
+            version(finder.find_highest_below("3.12.0")),
+            version(finder.find_highest_below("3.10.3")),
+            version(finder.find_highest_below("three")),
+        );
+
+        assert_eq!(minor, Some("3.11.2".to_string()));
+        assert_eq!(patch, Some("3.11.2".to_string()));
+        assert_eq!(floor, None);
+        assert_eq!(invalid, None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_highest_only() {
+        let dir = TempDir::new("highest-py");
+        let mut finder = Finder::default().select_providers(&[]).unwrap();
+        for version in ["3.10.4", "3.11.2", "3.11.7", "3.12.1"] {
+            fake_python(&dir, &format!("{}/python3", version), version);
+            finder = finder.add_search_path(dir.join(version));
+        }
+
+        let versions = |options: MatchOptions| -> Vec<String> {
+            finder
+                .find_all(options)
+                .iter()
+                .map(|p| p.version().unwrap().to_string())
+                .collect()
+        };
+        let (all, newest, newest_minor) = (
+            versions(MatchOptions::default().version_spec("3")),
+            versions(MatchOptions::default().version_spec("3").highest_only(true)),
+            versions(
+                MatchOptions::default()
+                    .version_spec("3.11")
+                    .highest_only(true),
+            ),
+        );
+
+        assert_eq!(all.len(), 4);
+        assert_eq!(newest, vec!["3.12.1"]);
+        assert_eq!(newest_minor, vec!["3.11.7"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_require_dev() {
+        let dir = TempDir::new("dev");
+        for prefix in ["full", "no-headers", "no-config"] {
+            std::fs::create_dir_all(dir.join(prefix).join("bin")).unwrap();
+            std::fs::write(dir.join(prefix).join("bin/python3.12"), "").unwrap();
+        }
+        for prefix in ["full", "no-config"] {
+            std::fs::create_dir_all(dir.join(prefix).join("include/python3.12")).unwrap();
+            std::fs::write(dir.join(prefix).join("include/python3.12/Python.h"), "").unwrap();
+        }
+        for prefix in ["full", "no-headers"] {
+            std::fs::write(dir.join(prefix).join("bin/python3.12-config"), "").unwrap();
+        }
+        let python = |prefix: &str| PythonVersion::new(dir.join(prefix).join("bin/python3.12"));
+        let options = MatchOptions::default().require_dev(true);
+        let (full, no_headers, no_config) = (
+            python("full").matches_unversioned(&options),
+            python("no-headers").matches_unversioned(&options),
+            python("no-config").matches_unversioned(&options),
+        );
+
+        assert!(full);
+        assert!(!no_headers);
+        assert!(!no_config);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_without_probing() {
+        let dir = TempDir::new("unprobed");
+        // Not runnable, so only found when it isn't probed
+        std::fs::write(dir.join("python3"), "").unwrap();
+        let finder = Finder::default()
+            .select_providers(&[])
+            .unwrap()
+            .add_search_path(dir.to_path_buf())
+            .add_search_path(dir.to_path_buf());
+
+        let unprobed = finder.find_all(
+            MatchOptions::default()
+                .version_spec("3.12")
+                .probe_versions(false),
+        );
+        let probed = finder.find_all(MatchOptions::default());
+        let misnamed = finder.find_all(MatchOptions::default().name("pypy").probe_versions(false));
+
+        let paths: Vec<PathBuf> = unprobed.into_iter().map(|p| p.executable).collect();
+        assert_eq!(paths, vec![dir.join("python3")]);
+        assert!(probed.is_empty());
+        assert!(misnamed.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_iter() {
+        let dir = TempDir::new("iter");
+        let mut finder = Finder::default().select_providers(&[]).unwrap();
+        for version in ["3.10.4", "3.12.1", "3.11.2"] {
+            fake_python(&dir, &format!("{}/python3", version), version);
+            finder = finder.add_search_path(dir.join(version));
+        }
+        // The same directory twice only yields its interpreter once
+        let finder = finder.add_search_path(dir.join("3.10.4"));
+
+        let versions: Vec<String> = finder
+            .iter(MatchOptions::default().version_spec("3"))
+            .map(|p| p.version().unwrap().to_string())
+            .collect();
+        let first = finder
+            .iter(MatchOptions::default().version_spec("3.12"))
+            .next()
+            .map(|p| p.version().unwrap().to_string());
+
+        assert_eq!(versions, vec!["3.10.4", "3.12.1", "3.11.2"]);
+        assert_eq!(first, Some("3.12.1".to_string()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_max_per_provider() {
+        let dir = TempDir::new("cap");
+        let mut finder = Finder::default().select_providers(&[]).unwrap();
+        for version in ["3.10.4", "3.11.2", "3.12.1"] {
+            fake_python(&dir, &format!("{}/python3", version), version);
+            finder = finder.add_search_path(dir.join(version));
+        }
+
+        let found = finder
+            .with_max_per_provider(2)
+            .find_all(MatchOptions::default());
+
+        let versions: Vec<String> = found
+            .iter()
+            .map(|p| p.version().unwrap().to_string())
+            .collect();
+        assert_eq!(versions, vec!["3.11.2", "3.10.4"]);
+    }
+
+    #[test]
+    fn test_provider_timeout() {
+        struct SlowProvider;
+
+        impl Provider for SlowProvider {
+            fn create() -> Option<Self> {
+                Some(SlowProvider)
+            }
+
+            fn find_pythons(&self) -> Vec<PythonVersion> {
+                std::thread::sleep(Duration::from_secs(5));
+                vec![PythonVersion::new(PathBuf::from("python3"))]
+            }
+        }
+
+        let mut finder = Finder::default()
+            .select_providers(&[])
+            .unwrap()
+            .with_provider_timeout(Duration::from_millis(100));
+        finder
+            .providers
+            .push(("slow".to_string(), Arc::new(SlowProvider)));
+
+        let start = std::time::Instant::now();
+        assert!(finder.find_all_python_versions().is_empty());
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_require_modules() {
+        // Each fake interpreter can only import its own module, the probe script
+        // being the second argument after `-EsSc`. ssl is probed through `_ssl`
+        let fixtures = [("with-pip", "pip"), ("with-tkinter", "tkinter"), ("with-ssl", "_ssl")];
+        let dir = TempDir::new("modules");
+        for (name, module) in fixtures {
+            let script = format!(
+                "case \"$2\" in\n  *\"'{}'\"*) echo True ;;\n  *) echo False ;;\nesac",
+                module
+            );
+            fake_script(&dir, name, &script);
+        }
+        let python = |name: &str| {
+            PythonVersion::new(dir.join(name))
+                .with_version(pep440_rs::Version::from_str("3.12.1").unwrap())
+        };
+
+        type HasModule = fn(&PythonVersion) -> bool;
+        let cases: [(&str, MatchOptions, HasModule); 3] = [
+            ("with-pip", MatchOptions::default().require_pip(true), PythonVersion::has_pip),
+            ("with-tkinter", MatchOptions::default().require_tkinter(true), PythonVersion::has_tkinter),
+            ("with-ssl", MatchOptions::default().require_ssl(true), PythonVersion::has_ssl),
+        ];
+        for (expected, options, has_module) in cases {
+            for (name, _) in fixtures {
+                assert_eq!(has_module(&python(name)), name == expected, "{} for {}", name, expected);
+                assert_eq!(python(name).matches(&options), name == expected, "{} for {}", name, expected);
+            }
+        }
+        assert!(python("with-ssl").matches(&MatchOptions::default()));
+    }
+
+    #[test]
+    fn test_venv() {
+        let dir = TempDir::new("venv");
+        // The marker is found two levels up (`<venv>/bin/python3`) or one level up
+        // (`<venv>/python.exe` on Windows), but not any higher
+        let fixtures = [
+            ("two-levels/bin", Some("two-levels")),
+            ("one-level", Some("one-level")),
+            ("too-deep/a/bin", Some("too-deep")),
+            ("system/bin", None),
+        ];
+        for (bin, venv) in fixtures {
+            std::fs::create_dir_all(dir.join(bin)).unwrap();
+            std::fs::write(dir.join(bin).join("python3"), "").unwrap();
+            if let Some(venv) = venv {
+                std::fs::write(dir.join(venv).join("pyvenv.cfg"), "home = /usr/bin\n").unwrap();
+            }
+        }
+        let python = |bin: &str| {
+            PythonVersion::new(dir.join(bin).join("python3"))
+                .with_version(pep440_rs::Version::from_str("3.12.1").unwrap())
+        };
+
+        let found: Vec<(bool, bool, bool)> = fixtures
+            .iter()
+            .map(|(bin, _)| {
+                (
+                    python(bin).is_venv(),
+                    python(bin).matches(&MatchOptions::default().venv(true)),
+                    python(bin).matches(&MatchOptions::default().venv(false)),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            found,
+            vec![
+                (true, true, false),
+                (true, true, false),
+                (false, false, true),
+                (false, false, true)
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_include_invalid() {
+        let dir = TempDir::new("invalid");
+        let fixtures = [
+            ("broken/python3", "exit 1"),
+            ("broken/pypy3", "exit 1"),
+            ("old/python3", "echo 3.11.2"),
+            ("new/python3", "echo 3.12.1"),
+        ];
+        for (path, body) in fixtures {
+            fake_script(&dir, path, body);
+        }
+        let finder = Finder::default()
+            .select_providers(&[])
+            .unwrap()
+            .add_explicit(fixtures.iter().map(|(path, _)| dir.join(path)).collect());
+        let paths = |pythons: Vec<PythonVersion>| -> Vec<PathBuf> {
+            pythons.into_iter().map(|p| p.executable).collect()
+        };
+
+        let valid = paths(finder.find_all(MatchOptions::default().name("python3")));
+        let finder = finder.with_include_invalid(true);
+        let all = paths(finder.find_all(MatchOptions::default()));
+        let named = paths(finder.find_all(MatchOptions::default().name("python3")));
+
+        // Invalid interpreters are kept after the valid ones, still filtered by name
+        assert_eq!(valid, vec![dir.join("new/python3"), dir.join("old/python3")]);
+        assert_eq!(all.len(), 4);
+        assert_eq!(&all[..2], &valid[..]);
+        assert_eq!(
+            named,
+            vec![dir.join("new/python3"), dir.join("old/python3"), dir.join("broken/python3")]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_without_dedup() {
+        let dir = TempDir::new("dedup");
+        fake_script(&dir, "python3", "exit 1");
+
+        let finder = Finder::default()
+            .select_providers(&[])
+            .unwrap()
+            .add_search_path(dir.to_path_buf())
+            .add_search_path(dir.to_path_buf())
+            .with_include_invalid(true);
+        assert_eq!(finder.find_all(MatchOptions::default()).len(), 1);
+        assert_eq!(finder.find_all(MatchOptions::default().dedup(false)).len(), 2);
+        assert_eq!(finder.with_dedup(false).find_all(MatchOptions::default()).len(), 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hanging_interpreter_times_out() {
+        use std::time::{Duration, Instant};
+
+        let dir = TempDir::new("hang");
+        fake_script(&dir, "python3", "exec sleep 30");
+
+        let finder = Finder::default()
+            .select_providers(&[])
+            .unwrap()
+            .add_search_path(dir.to_path_buf())
+            .with_probe_timeout(1);
+
+        let start = Instant::now();
+        assert!(finder.find_all(MatchOptions::default()).is_empty());
+        assert!(finder
+            .find_all(MatchOptions::default().architecture("64bit"))
+            .is_empty());
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
 }
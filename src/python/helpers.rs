@@ -44,6 +44,8 @@ lazy_static! {
     .unwrap();
 }
 
+/// Whether the file name looks like a Python interpreter (`python3.11`, `pypy3`,
+/// `python.exe`, ...) and the file is executable or has a known executable extension.
 pub fn path_is_python(path: &PathBuf) -> bool {
     looks_like_python(path.file_name().unwrap_or_default()) && path_is_known_executable(path)
 }
@@ -56,7 +58,7 @@ fn looks_like_python(name: &OsStr) -> bool {
 
 fn path_is_known_executable(path: &PathBuf) -> bool {
     if let Ok(path_meta) = path.metadata() {
-        let extension = path.extension().map(|e| e.to_str().unwrap().to_lowercase());
+        let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase());
 
         path_meta.is_file() && path.access(AccessMode::READ | AccessMode::EXECUTE).is_ok()
             || extension.map_or(true, |e| KNOWN_EXECUTABLES.contains(&e.as_str()))
@@ -66,15 +68,22 @@ fn path_is_known_executable(path: &PathBuf) -> bool {
     }
 }
 
-pub fn calculate_file_hash(path: &PathBuf) -> Result<String, io::Error> {
-    let mut file = std::fs::File::open(path)?;
+/// MD5 of the file's contents. The file is streamed through the hasher so large
+/// binaries are never loaded into memory at once.
+pub(crate) fn calculate_file_hash(path: &PathBuf) -> Result<String, io::Error> {
+    let mut file = io::BufReader::with_capacity(64 * 1024, std::fs::File::open(path)?);
     let mut hasher = md5::Context::new();
     std::io::copy(&mut file, &mut hasher)?;
     Ok(format!("{:x}", hasher.compute()))
 }
 
+/// Rank of the executable's extension, lower is preferred. Paths without an
+/// extension (or with an unknown one) rank 0, i.e. first, then the known
+/// executable extensions in order: `exe`, `py`, `bat` on Windows and `sh`, `bash`,
+/// `csh`, `zsh`, `fish`, `py` elsewhere. The finder uses it to pick the path kept
+/// among duplicates of one interpreter.
 pub fn suffix_preference(path: &PathBuf) -> usize {
-    let ext = path.extension().map(|e| e.to_str().unwrap().to_lowercase());
+    let ext = path.extension().map(|e| e.to_string_lossy().to_lowercase());
     if let Some(ext) = ext {
         KNOWN_EXECUTABLES
             .iter()
@@ -84,3 +93,18 @@ pub fn suffix_preference(path: &PathBuf) -> usize {
         0
     }
 }
+
+/// Normalize an architecture name so that aliases compare equal:
+/// `amd64`/`x64` -> `x86_64`, `i386`..`i686` -> `x86`, `arm64` -> `aarch64`,
+/// `64`/`32` -> `64bit`/`32bit`. Unknown names are only lowercased.
+pub(crate) fn normalize_architecture(arch: &str) -> String {
+    let arch = arch.trim().to_lowercase();
+    match arch.as_str() {
+        "amd64" | "x64" | "x86-64" => "x86_64".to_string(),
+        "i386" | "i486" | "i586" | "i686" => "x86".to_string(),
+        "arm64" => "aarch64".to_string(),
+        "64" => "64bit".to_string(),
+        "32" => "32bit".to_string(),
+        _ => arch,
+    }
+}
@@ -1,35 +1,408 @@
 mod providers;
 mod finder;
-mod helpers;
+pub mod helpers;
 mod python;
+#[cfg(test)]
+mod testing;
 
-pub use finder::MatchOptions;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+pub use finder::{Finder, MatchOptions, PrereleaseKind, Tiebreak};
+pub use python::PythonVersion;
+pub use providers::available_providers;
 
 #[cfg(feature = "node-compile")]
 use napi_derive::napi;
 
 // Evaluated, simplified version of python::PythonVersion
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "node-compile", napi)]
 pub struct Version {
     pub executable: String,
     pub formatted_name: Option<String>,
-    pub version: Option<String>
+    /// The provider that found the interpreter (e.g. `path`, `pyenv`, `conda`)
+    pub provider: Option<String>,
+    pub version: Option<String>,
+    /// The bitness (`64bit` or `32bit`) if it was already known, e.g. from the registry
+    /// or an architecture filter. Interpreters aren't run again just for this.
+    pub architecture: Option<String>,
+    /// The PEP 508 environment markers, only probed with `MatchOptions::probe_details`
+    pub markers: Option<HashMap<String, String>>,
+    /// The ABI tag, e.g. `cpython-311-x86_64-linux-gnu`, probed along with `markers`
+    pub soabi: Option<String>,
+    /// The `site-packages` directory, probed along with `markers`
+    pub site_packages: Option<String>,
+    /// `glibc` or `musl` for standalone builds, see `PythonVersion::libc`
+    pub libc: Option<String>,
+    pub is_venv: bool,
+    /// Whether this is the version selected by pyenv, see `PythonVersion::is_default`
+    pub is_default: bool
 }
 
-pub fn run(args: MatchOptions) -> Vec<Version> {
-    let finder = finder::Finder::default();
-    finder
-        .find_all(args)
-        .into_iter()
-        .map(|v| Version {
-            executable: String::from(v.executable.to_str().unwrap()),
+impl Version {
+    /// The details known without running the interpreter, see
+    /// `MatchOptions::probe_versions`
+    pub fn unprobed(v: &PythonVersion) -> Self {
+        Version {
+            executable: v.executable.to_string_lossy().to_string(),
             formatted_name: v.formatted_name.clone(),
+            provider: v.provider.clone(),
+            version: None,
+            architecture: v.known_architecture(),
+            markers: None,
+            soabi: None,
+            site_packages: None,
+            libc: v.libc().map(|libc| libc.to_string()),
+            is_venv: v.is_venv(),
+            is_default: v.is_default
+        }
+    }
+
+    /// Same as `From`, also probing the details left out by default, see
+    /// `MatchOptions::probe_details`
+    pub fn detailed(v: &PythonVersion) -> Self {
+        Version {
+            markers: v.markers().ok(),
+            soabi: v.soabi().ok(),
+            site_packages: v
+                .site_packages()
+                .ok()
+                .map(|p| p.to_string_lossy().to_string()),
+            ..Version::from(v)
+        }
+    }
+
+    /// The first three release components of `version`, missing ones being 0 -
+    /// e.g. (3, 12, 0) for 3.12. `None` when the version is unknown or invalid.
+    pub fn release_tuple(&self) -> Option<(usize, usize, usize)> {
+        let version = pep440_rs::Version::from_str(self.version.as_deref()?).ok()?;
+        let part = |i: usize| version.release.get(i).copied().unwrap_or_default();
+        Some((part(0), part(1), part(2)))
+    }
+
+    /// A command running this interpreter, without a console window on Windows
+    pub fn command(&self) -> std::process::Command {
+        python::python_command(&self.executable)
+    }
+}
+
+impl From<&PythonVersion> for Version {
+    fn from(v: &PythonVersion) -> Self {
+        Version {
+            executable: v.executable.to_string_lossy().to_string(),
+            formatted_name: v.formatted_name.clone(),
+            provider: v.provider.clone(),
             version: match v.version() {
                 Ok(v) => Some(v.to_string()),
                 Err(_) => None
-            }
-        })
+            },
+            architecture: v.known_architecture(),
+            markers: None,
+            soabi: None,
+            site_packages: None,
+            libc: v.libc().map(|libc| libc.to_string()),
+            is_venv: v.is_venv(),
+            is_default: v.is_default
+        }
+    }
+}
+
+pub fn run(args: MatchOptions) -> Vec<Version> {
+    run_with_finder(Finder::default(), args)
+}
+
+/// Same as `run`, with the extra search paths and disabled providers of the JSON
+/// config file at `path` merged in. A missing file is the same as an empty one.
+#[cfg(feature = "config")]
+pub fn run_with_config(path: &std::path::Path, args: MatchOptions) -> std::io::Result<Vec<Version>> {
+    let config = crate::config::Config::load(path)?;
+    let mut args = args;
+    args.extra_paths.extend(config.python_paths);
+    let disabled: Vec<&str> = config.disabled_providers.iter().map(|n| n.as_str()).collect();
+    Ok(run_with_finder(Finder::default().disable_providers(&disabled), args))
+}
+
+fn run_with_finder(finder: Finder, args: MatchOptions) -> Vec<Version> {
+    let to_version = version_converter(&args);
+    configure_finder(finder, &args)
+        .find_all(args)
+        .iter()
+        .map(to_version)
         .collect()
 }
 
+// Converts without running the interpreters when they aren't probed, and only
+// probes the details when asked to
+fn version_converter(args: &MatchOptions) -> fn(&PythonVersion) -> Version {
+    match (args.probe_versions, args.probe_details) {
+        (Some(false), _) => Version::unprobed,
+        (_, Some(true)) => Version::detailed,
+        _ => |v| Version::from(v)
+    }
+}
+
+// Applies the options that configure the finder rather than filter interpreters
+fn configure_finder(finder: Finder, args: &MatchOptions) -> Finder {
+    let finder = args
+        .extra_paths
+        .iter()
+        .fold(finder.with_fast_mode(args.fast_mode.unwrap_or(false)), |f, p| f.add_search_path(p.into()))
+        .with_deep_path_scan(args.deep_path_scan.unwrap_or(false))
+        .same_file(!args.dedup_by_content.unwrap_or(false))
+        .with_tiebreak(args.tiebreak.unwrap_or_default());
+    match args.max_per_provider {
+        Some(max) => finder.with_max_per_provider(max),
+        None => finder,
+    }
+}
+
+/// Writes each matching interpreter to `writer` as a JSON object on its own line
+/// (JSON Lines), flushing after each one, so a consumer can start before the
+/// discovery finishes. Interpreters are written in discovery order, see
+/// `Finder::iter`.
+#[cfg(feature = "serde")]
+pub fn write_jsonl<W: std::io::Write>(args: MatchOptions, writer: &mut W) -> std::io::Result<()> {
+    write_jsonl_with_finder(Finder::default(), args, writer)
+}
+
+#[cfg(feature = "serde")]
+fn write_jsonl_with_finder<W: std::io::Write>(finder: Finder, args: MatchOptions, writer: &mut W) -> std::io::Result<()> {
+    let finder = configure_finder(finder, &args);
+    let to_version = version_converter(&args);
+    // Held across the loop, as the iterator is consumed on this thread
+    let _probe_cache = python::ProbeCacheScope::enter();
+    for python in finder.iter(args) {
+        serde_json::to_writer(&mut *writer, &to_version(&python))?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+/// Same as `run`, with the interpreters grouped by their (major, minor) version in
+/// ascending order, e.g. for a version picker. Each group keeps the order of `run`,
+/// and interpreters whose version is unknown are left out.
+pub fn run_grouped(args: MatchOptions) -> BTreeMap<(usize, usize), Vec<Version>> {
+    let mut groups: BTreeMap<(usize, usize), Vec<Version>> = BTreeMap::new();
+    for version in run(args) {
+        if let Some((major, minor, _)) = version.release_tuple() {
+            groups.entry((major, minor)).or_default().push(version);
+        }
+    }
+    groups
+}
+
+/// Same as `run`, but probes the interpreters on tokio's blocking thread pool
+#[cfg(feature = "async")]
+pub async fn run_async(args: MatchOptions) -> Vec<Version> {
+    match tokio::task::spawn_blocking(move || run(args)).await {
+        Ok(versions) => versions,
+        Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+        Err(_) => vec![]
+    }
+}
+
+/// Refreshes the interpreters of a previous run (e.g. deserialized from a cache)
+/// without running them again: those whose executable is gone are dropped, and the
+/// interpreters the providers now find elsewhere are probed and added after the
+/// cached ones, newest first. Only the new interpreters are run, and they are
+/// filtered and converted with `args` like in `run`.
+pub fn reconcile(cached: Vec<Version>, args: MatchOptions) -> Vec<Version> {
+    let finder = configure_finder(Finder::default(), &args);
+    let candidates = finder.find_all(MatchOptions::default().probe_versions(false));
+    reconcile_versions(cached, candidates, &finder, args)
+}
+
+fn reconcile_versions(cached: Vec<Version>, candidates: Vec<PythonVersion>, finder: &Finder, args: MatchOptions) -> Vec<Version> {
+    let real_path = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let mut versions: Vec<Version> = cached
+        .into_iter()
+        .filter(|v| Path::new(&v.executable).is_file())
+        .collect();
+    let mut known: HashSet<PathBuf> = versions
+        .iter()
+        .map(|v| real_path(Path::new(&v.executable)))
+        .collect();
+
+    // Probed together, so that links to the same interpreter are collapsed
+    let added: Vec<PythonVersion> = candidates
+        .into_iter()
+        .filter(|python| known.insert(real_path(&python.executable)))
+        .collect();
+    let to_version = version_converter(&args);
+    versions.extend(finder.select(added, args).iter().map(to_version));
+    versions
+}
+
+/// Parses a version the way the version reported by an interpreter is: surrounding
+/// whitespace and a `+local` suffix (as in `3.12.1+`) are ignored. `None` when it
+/// isn't a valid PEP 440 version.
+pub fn normalize_version(version: &str) -> Option<pep440_rs::Version> {
+    let version = version.trim().split('+').next().unwrap_or_default();
+    pep440_rs::Version::from_str(version).ok()
+}
+
+/// Evaluate the interpreter at `path` without running the providers, `None`
+/// when it isn't a runnable interpreter. The details are left out like in `run`,
+/// see `Version::detailed`.
+pub fn inspect(path: &str) -> Option<Version> {
+    let python = PythonVersion::new(path.into());
+    if python.is_valid() {
+        Some(Version::from(&python))
+    } else {
+        None
+    }
+}
+
+/// Find the interpreter a shell would run for a bare `python3` (or `python`),
+/// i.e. the first runnable one in `PATH` order. Unlike `run`, results are not
+/// reordered by version.
+pub fn find_default() -> Option<Version> {
+    let names: &[&str] = if cfg!(windows) {
+        &["python3.exe", "python.exe"]
+    } else {
+        &["python3", "python"]
+    };
+    let path_env = std::env::var_os("PATH").unwrap_or_default();
+    std::env::split_paths(&path_env)
+        .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+        .filter(|path| path.is_file())
+        .map(PythonVersion::new)
+        .find(|python| python.is_valid())
+        .map(|python| Version::from(&python))
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[cfg(unix)]
+    use testing::{fake_python, fake_script, TempDir};
+
+    #[test]
+    fn test_release_tuple() {
+        let version = |v: Option<&str>| Version {
+            version: v.map(|v| v.to_string()),
+            ..Version::unprobed(&PythonVersion::new("python3".into()))
+        };
+        assert_eq!(version(Some("3.12.1")).release_tuple(), Some((3, 12, 1)));
+        assert_eq!(version(Some("3.13.0rc1")).release_tuple(), Some((3, 13, 0)));
+        assert_eq!(version(Some("3.12")).release_tuple(), Some((3, 12, 0)));
+        assert_eq!(version(Some("unknown")).release_tuple(), None);
+        assert_eq!(version(None).release_tuple(), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_reconcile() {
+        let dir = TempDir::new("reconcile-py");
+        for version in ["3.11.2", "3.12.1"] {
+            fake_python(&dir, &format!("python{}", &version[..4]), version);
+        }
+        std::os::unix::fs::symlink(dir.join("python3.12"), dir.join("python3")).unwrap();
+        let cached = |path: &str, version: &str| Version {
+            version: Some(version.to_string()),
+            ..Version::unprobed(&PythonVersion::new(dir.join(path)))
+        };
+        let candidates = || {
+            ["python3", "python3.11", "python3.12"]
+                .iter()
+                .map(|name| PythonVersion::new(dir.join(name)))
+                .collect()
+        };
+        let finder = Finder::default().select_providers(&[]).unwrap();
+
+        // The cached version is trusted, so the 3.12 install isn't run again
+        let versions = reconcile_versions(
+            vec![cached("python3.12", "3.12.0"), cached("python3.10", "3.10.4")],
+            candidates(),
+            &finder,
+            MatchOptions::default()
+        );
+        // Additions are filtered like in `run`, the cached interpreters are kept
+        let filtered = reconcile_versions(
+            vec![cached("python3.12", "3.12.0")],
+            candidates(),
+            &finder,
+            MatchOptions::default().version_spec("3.12")
+        );
+
+        let found: Vec<(String, Option<String>)> = versions
+            .into_iter()
+            .map(|v| (v.executable, v.version))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (dir.join("python3.12").to_str().unwrap().to_string(), Some("3.12.0".to_string())),
+                (dir.join("python3.11").to_str().unwrap().to_string(), Some("3.11.2".to_string()))
+            ]
+        );
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].version, Some("3.12.0".to_string()));
+    }
+
+    #[cfg(all(unix, feature = "serde"))]
+    #[test]
+    fn test_write_jsonl() {
+        let dir = TempDir::new("jsonl-py");
+        let python = fake_python(&dir, "python3", "3.12.1");
+        let finder = Finder::default().select_providers(&[]).unwrap();
+
+        let mut out = vec![];
+        let args = MatchOptions::default().extra_path(dir.to_str().unwrap());
+        write_jsonl_with_finder(finder, args, &mut out).unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<Version> = out.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert!(out.ends_with('\n'));
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].executable, python.to_str().unwrap());
+        assert_eq!(lines[0].version, Some("3.12.1".to_string()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_probe_details() {
+        let dir = TempDir::new("details");
+        let counter = dir.join("probes");
+        // Answers every probe with the details, then the version
+        let script = format!(
+            "echo x >> {}\nprintf '{}'",
+            counter.display(),
+            "sys_platform=linux\\nsoabi=cpython-312-x86_64-linux-gnu\\nsite_packages=/opt/site-packages\\n3.12.1\\n"
+        );
+        let python = fake_script(&dir, "python3", &script);
+        let probes = || std::fs::read_to_string(&counter).unwrap_or_default().lines().count();
+
+        let plain = Version::from(&PythonVersion::new(python.clone()));
+        let plain_probes = probes();
+        let detailed = Version::detailed(&PythonVersion::new(python.clone()));
+        let detailed_probes = probes() - plain_probes;
+
+        assert_eq!(plain.version.as_deref(), Some("3.12.1"));
+        assert_eq!((plain.markers, plain.soabi, plain.site_packages), (None, None, None));
+        assert_eq!(plain_probes, 1);
+
+        // The details take a single run on top of the version probe
+        assert_eq!(detailed.version.as_deref(), Some("3.12.1"));
+        assert_eq!(detailed.soabi.as_deref(), Some("cpython-312-x86_64-linux-gnu"));
+        assert_eq!(detailed.site_packages.as_deref(), Some("/opt/site-packages"));
+        let markers = detailed.markers.unwrap();
+        assert_eq!(markers["sys_platform"], "linux");
+        assert!(!markers.contains_key("soabi"));
+        assert_eq!(detailed_probes, 2);
+    }
+
+    #[test]
+    fn test_normalize_version() {
+        let normalized = |v: &str| normalize_version(v).map(|v| v.to_string());
+        assert_eq!(normalized(" 3.12.1\n"), Some("3.12.1".to_string()));
+        assert_eq!(normalized("3.13.0+"), Some("3.13.0".to_string()));
+        assert_eq!(normalized("3.13.0rc1"), Some("3.13.0rc1".to_string()));
+        assert_eq!(normalized("banner"), None);
+    }
+}
@@ -19,14 +19,15 @@ impl AsdfProvider {
 impl Provider for AsdfProvider {
     fn create() -> Option<Self> {
         let pyenv_root = std::env::var_os("ASDF_DATA_DIR").unwrap_or("$HOME/.asdf".into());
+        let pyenv_root = pyenv_root.to_string_lossy();
 
         let root =
-            shellexpand::env_with_context_no_errors(pyenv_root.to_str().unwrap(), |var_name| {
+            shellexpand::env_with_context_no_errors(&pyenv_root, |var_name| {
                 let s = match var_name {
                     "HOME" => dirs::home_dir()?.into_os_string(),
                     var => std::env::var_os(var)?,
                 };
-                Some(s.into_string().unwrap())
+                s.into_string().ok()
             });
 
         let path = PathBuf::from(root.into_owned());
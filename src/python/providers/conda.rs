@@ -1,59 +1,86 @@
-use std::{fmt::Debug, path::PathBuf};
+use std::{fmt::Debug, path::{Path, PathBuf}};
 
 use super::Provider;
 
 use crate::python::python::PythonVersion;
 
+/// Searches conda installations: the base environment of each install root and the
+/// environments in its `envs` directory, plus the active environment (`CONDA_PREFIX`)
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(super) struct CondaProvider {
     roots: Vec<PathBuf>,
+    prefixes: Vec<PathBuf>,
 }
 
 impl CondaProvider {
-    pub fn new(roots: Vec<PathBuf>) -> Self {
-        Self { roots }
+    pub fn new(roots: Vec<PathBuf>, prefixes: Vec<PathBuf>) -> Self {
+        Self { roots, prefixes }
     }
 }
 
+// The interpreters of a single environment, tagged with its name
+fn find_env_pythons(env: &Path, name: &str) -> Vec<PythonVersion> {
+    let bin = if cfg!(windows) {
+        env.to_path_buf()
+    } else {
+        env.join("bin")
+    };
+    let mut found = super::find_pythons_from_path(&bin, true);
+    found.iter_mut()
+        .for_each(|v| v.formatted_name = Some(format!("Conda '{}'", name)));
+    found
+}
+
 impl Provider for CondaProvider {
     fn create() -> Option<Self> {
-        let roots = vec![
-            std::env::var_os("CONDA_ROOT")
-                .or_else(|| Some(dirs::home_dir()?.join(".conda").join("envs").into_os_string()))?,
-            dirs::home_dir()?.join("miniconda3").join("envs").into_os_string(),
-            dirs::home_dir()?.join("anaconda3").join("envs").into_os_string(),
-            dirs::home_dir()?.join("conda").join("envs").into_os_string()
-        ];
-            
-        Some(Self::new(
-            roots
-                .into_iter()
-                .map(|r| PathBuf::from(r))
-                .collect()
-        ))
+        let mut roots: Vec<PathBuf> = ["CONDA_ROOT", "MAMBA_ROOT_PREFIX"]
+            .iter()
+            .filter_map(|var| std::env::var_os(var))
+            .map(PathBuf::from)
+            .collect();
+        if let Some(home) = dirs::home_dir() {
+            for dir in [".conda", "miniconda3", "anaconda3", "conda", "miniforge3", "mambaforge"] {
+                roots.push(home.join(dir));
+            }
+        }
+        // System-wide installs, as in most data science container images
+        if cfg!(unix) {
+            roots.push(PathBuf::from("/opt/conda"));
+        }
+        // CONDA_ROOT usually points at one of the default roots
+        let mut unique: Vec<PathBuf> = vec![];
+        roots.retain(|root| {
+            let new = !unique.contains(root);
+            unique.push(root.clone());
+            new
+        });
+
+        let prefixes = std::env::var_os("CONDA_PREFIX")
+            .map(PathBuf::from)
+            .into_iter()
+            .collect();
+        Some(Self::new(roots, prefixes))
     }
 
     fn find_pythons(&self) -> Vec<PythonVersion> {
         let mut versions = vec![];
 
+        for prefix in &self.prefixes {
+            let name = prefix.file_name().and_then(|n| n.to_str()).unwrap_or("base");
+            versions.extend(find_env_pythons(prefix, name));
+        }
+
         for root in &self.roots {
-            versions.extend(match root.read_dir() {
+            versions.extend(find_env_pythons(root, "base"));
+            versions.extend(match root.join("envs").read_dir() {
                 Ok(entries) => entries
                     .into_iter()
                     .flat_map(|entry| match entry {
                         Ok(entry) => {
                             let path = entry.path();
-                            let env = path.file_name().unwrap().to_str().unwrap();
+                            let env = path.file_name().unwrap_or_default().to_string_lossy();
                             if path.is_dir() {
-                                let bin = if cfg!(windows) {
-                                    path.clone()
-                                } else {
-                                    path.join("bin")
-                                };
-                                let mut found = super::find_pythons_from_path(&bin, true);
-                                found.iter_mut()
-                                    .for_each(|v| v.formatted_name = Some(format!("Conda '{}'", env)));
-                                found
+                                find_env_pythons(&path, &env)
                             } else {
                                 vec![]
                             }
@@ -68,3 +95,28 @@ impl Provider for CondaProvider {
         versions
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[cfg(unix)]
+    use crate::python::testing::TempDir;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_base_and_envs() {
+        let root = TempDir::new("conda");
+        std::fs::create_dir_all(root.join("bin")).unwrap();
+        std::fs::create_dir_all(root.join("envs/ml/bin")).unwrap();
+        std::fs::write(root.join("bin/python3"), "").unwrap();
+        std::fs::write(root.join("envs/ml/bin/python3"), "").unwrap();
+
+        let found = CondaProvider::new(vec![root.to_path_buf()], vec![]).find_pythons();
+
+        let names: Vec<Option<String>> = found.into_iter().map(|p| p.formatted_name).collect();
+        assert_eq!(
+            names,
+            vec![Some("Conda 'base'".to_string()), Some("Conda 'ml'".to_string())]
+        );
+    }
+}
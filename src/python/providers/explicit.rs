@@ -0,0 +1,35 @@
+use std::path::PathBuf;
+
+use super::Provider;
+use crate::python::python::PythonVersion;
+
+/// A provider returning interpreters given by their exact path, e.g. custom builds
+/// that no other provider would find. Only added through `Finder::add_explicit`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ExplicitProvider {
+    executables: Vec<PathBuf>,
+}
+
+impl ExplicitProvider {
+    pub fn new(executables: Vec<PathBuf>) -> Self {
+        Self { executables }
+    }
+}
+
+impl Provider for ExplicitProvider {
+    fn create() -> Option<Self>
+    where
+        Self: Sized,
+    {
+        // There is nothing to find without a list of paths
+        None
+    }
+
+    fn find_pythons(&self) -> Vec<PythonVersion> {
+        self.executables
+            .iter()
+            .filter(|executable| executable.is_file())
+            .map(|executable| PythonVersion::new(executable.clone()))
+            .collect()
+    }
+}
@@ -0,0 +1,50 @@
+use std::path::PathBuf;
+
+use super::Provider;
+use crate::python::python::PythonVersion;
+
+/// A provider that searches the framework builds installed by the python.org
+/// installer, e.g. `/Library/Frameworks/Python.framework/Versions/3.12/bin/python3`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct FrameworkProvider {
+    roots: Vec<PathBuf>,
+}
+
+impl FrameworkProvider {
+    pub fn new(roots: Vec<PathBuf>) -> Self {
+        Self { roots }
+    }
+}
+
+impl Provider for FrameworkProvider {
+    fn create() -> Option<Self>
+    where
+        Self: Sized,
+    {
+        let mut roots = vec![PathBuf::from("/Library/Frameworks")];
+        if let Some(home) = dirs::home_dir() {
+            roots.push(home.join("Library").join("Frameworks"));
+        }
+        Some(Self::new(roots))
+    }
+
+    fn find_pythons(&self) -> Vec<PythonVersion> {
+        self.roots
+            .iter()
+            .filter_map(|root| root.join("Python.framework/Versions").read_dir().ok())
+            .flatten()
+            .filter_map(|entry| match entry {
+                // `Current` links to one of the other versions
+                Ok(entry) if !entry.path().is_symlink() => {
+                    let python = entry.path().join("bin/python3");
+                    if python.exists() {
+                        Some(PythonVersion::new(python.clone()).with_interpreter(python))
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
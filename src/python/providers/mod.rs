@@ -1,30 +1,58 @@
 // Heavily adapted from https://github.com/frostming/findpython
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::python::helpers;
 use crate::python::python::PythonVersion;
 use lazy_static::lazy_static;
 
 mod asdf;
+mod explicit;
 mod path;
 mod conda;
 mod pyenv;
 mod rye;
 
+#[cfg(target_os = "macos")]
+mod framework;
+#[cfg(not(windows))]
+mod prefix;
+#[cfg(windows)]
+mod scoop;
+#[cfg(windows)]
+mod store;
 #[cfg(windows)]
 mod winreg;
+#[cfg(any(windows, test))]
+mod wsl;
+
+pub(crate) use explicit::ExplicitProvider;
+pub(crate) use path::PathProvider;
+#[cfg(not(windows))]
+pub(crate) use prefix::PrefixScanProvider;
 
 #[cfg(windows)]
 lazy_static! {
-    pub static ref ALL_PROVIDERS: [&'static str; 6] = ["path", "conda", "pyenv", "rye", "asdf", "winreg"];
+    pub static ref ALL_PROVIDERS: [&'static str; 8] = ["path", "conda", "pyenv", "rye", "asdf", "scoop", "store", "winreg"];
 }
 
-#[cfg(not(windows))]
+#[cfg(target_os = "macos")]
+lazy_static! {
+    pub static ref ALL_PROVIDERS: [&'static str; 6] = ["path", "conda", "pyenv", "rye", "asdf", "python.org"];
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
 lazy_static! {
     pub static ref ALL_PROVIDERS: [&'static str; 5] = ["path", "conda", "pyenv", "rye", "asdf"];
 }
 
+/// The names of the providers available on this platform, as accepted by
+/// `Finder::select_providers`. Optional providers like `prefix` or `wsl` aren't included,
+/// see `Finder::enable_providers`.
+pub fn available_providers() -> Vec<&'static str> {
+    ALL_PROVIDERS.to_vec()
+}
+
 pub trait Provider: Send + Sync {
     fn create() -> Option<Self>
     where
@@ -40,8 +68,18 @@ pub fn get_provider(name: &str) -> Option<Box<dyn Provider>> {
         "pyenv" => pyenv::PyenvProvider::create().map(|p| Box::new(p) as Box<dyn Provider>),
         "rye" => rye::RyeProvider::create().map(|p| Box::new(p) as Box<dyn Provider>),
         "asdf" => asdf::AsdfProvider::create().map(|p| Box::new(p) as Box<dyn Provider>),
+        #[cfg(target_os = "macos")]
+        "python.org" => framework::FrameworkProvider::create().map(|p| Box::new(p) as Box<dyn Provider>),
+        #[cfg(not(windows))]
+        "prefix" => prefix::PrefixScanProvider::create().map(|p| Box::new(p) as Box<dyn Provider>),
+        #[cfg(windows)]
+        "scoop" => scoop::ScoopProvider::create().map(|p| Box::new(p) as Box<dyn Provider>),
+        #[cfg(windows)]
+        "store" => store::StoreProvider::create().map(|p| Box::new(p) as Box<dyn Provider>),
         #[cfg(windows)]
         "winreg" => winreg::WinRegProvider::create().map(|p| Box::new(p) as Box<dyn Provider>),
+        #[cfg(windows)]
+        "wsl" => wsl::WslProvider::create().map(|p| Box::new(p) as Box<dyn Provider>),
         _ => None,
     }
 }
@@ -78,3 +116,31 @@ pub fn find_pythons_from_path(path: &PathBuf, as_interpreter: bool) -> Vec<Pytho
         Err(_) => vec![],
     }
 }
+
+/// Find every Python interpreter directly in the given directory, unlike
+/// `find_pythons_from_path` which stops at the first one. Links to an interpreter
+/// of the directory are skipped in favor of the file they point at.
+pub fn find_all_pythons_from_path(path: &Path) -> Vec<PythonVersion> {
+    let mut candidates: Vec<PathBuf> = match path.read_dir() {
+        Ok(entries) => entries
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(helpers::path_is_python)
+            .collect(),
+        Err(_) => return vec![],
+    };
+    candidates.sort_by_key(|path| (path.is_symlink(), path.clone()));
+
+    let mut seen = vec![];
+    candidates
+        .into_iter()
+        .filter(|path| {
+            let real_path = path.canonicalize().unwrap_or_else(|_| path.clone());
+            if seen.contains(&real_path) {
+                return false;
+            }
+            seen.push(real_path);
+            true
+        })
+        .map(PythonVersion::new)
+        .collect()
+}
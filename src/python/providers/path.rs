@@ -7,16 +7,43 @@ use crate::python::python::PythonVersion;
 
 /// A provider that searches Python interpreters in the PATH.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub(super) struct PathProvider {
+pub(crate) struct PathProvider {
     paths: Vec<PathBuf>,
+    deep: bool,
 }
 
 impl PathProvider {
     pub fn new() -> Self {
         let path_env = std::env::var_os("PATH").unwrap_or_default();
-        Self {
-            paths: std::env::split_paths(&path_env).collect(),
+        let mut paths: Vec<PathBuf> = std::env::split_paths(&path_env).collect();
+        // Nix profiles link their interpreters from deep store paths, and are not always in PATH
+        for path in Self::nix_profile_paths() {
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
         }
+        Self { paths, deep: false }
+    }
+
+    /// Report every interpreter in each directory, e.g. `python3.11` and `python3.12`
+    /// next to `python3` in `/usr/bin`, rather than only the first one found
+    pub fn with_deep_scan(mut self, deep: bool) -> Self {
+        self.deep = deep;
+        self
+    }
+
+    #[cfg(unix)]
+    fn nix_profile_paths() -> Vec<PathBuf> {
+        let mut paths = vec![PathBuf::from("/run/current-system/sw/bin")];
+        if let Some(home) = dirs::home_dir() {
+            paths.insert(0, home.join(".nix-profile").join("bin"));
+        }
+        paths.into_iter().filter(|p| p.is_dir()).collect()
+    }
+
+    #[cfg(not(unix))]
+    fn nix_profile_paths() -> Vec<PathBuf> {
+        vec![]
     }
 }
 
@@ -28,7 +55,53 @@ impl Provider for PathProvider {
     fn find_pythons(&self) -> Vec<PythonVersion> {
         self.paths
             .iter()
-            .flat_map(|path| super::find_pythons_from_path(path, false))
+            .enumerate()
+            .flat_map(|(index, path)| {
+                let found = if self.deep {
+                    super::find_all_pythons_from_path(path)
+                } else {
+                    super::find_pythons_from_path(path, false)
+                };
+                found
+                    .into_iter()
+                    .map(move |python| python.with_path_index(index))
+            })
             .collect()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[cfg(unix)]
+    use crate::python::testing::{fake_script, TempDir};
+
+    #[cfg(unix)]
+    #[test]
+    fn test_deep_scan() {
+        let dir = TempDir::new("deep");
+        let bin = dir.join("usr/bin");
+        for name in ["python3.11", "python3.12", "python3.12-config", "pip3"] {
+            fake_script(&bin, name, "");
+        }
+        // A link to an interpreter already in the directory isn't reported again
+        std::os::unix::fs::symlink(bin.join("python3.12"), bin.join("python3")).unwrap();
+        std::os::unix::fs::symlink(bin.join("python3.12"), bin.join("python")).unwrap();
+
+        let provider = PathProvider {
+            paths: vec![bin.clone()],
+            deep: false,
+        };
+        let shallow = provider.find_pythons().len();
+        let mut deep: Vec<PathBuf> = provider
+            .with_deep_scan(true)
+            .find_pythons()
+            .into_iter()
+            .map(|p| p.executable)
+            .collect();
+        deep.sort();
+
+        assert_eq!(shallow, 1);
+        assert_eq!(deep, vec![bin.join("python3.11"), bin.join("python3.12")]);
+    }
+}
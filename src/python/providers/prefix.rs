@@ -0,0 +1,95 @@
+use std::path::PathBuf;
+
+use super::{find_pythons_from_path, Provider};
+use crate::python::python::PythonVersion;
+
+/// Where container images usually install interpreters, e.g. the official `python`
+/// image or the manylinux images
+static DEFAULT_ROOTS: [&str; 4] = [
+    "/usr/local/bin",
+    "/opt/python/*/bin",
+    "/opt/pythons/*/bin",
+    "/opt/conda/bin",
+];
+
+/// A provider that searches fixed install prefixes, useful in minimal containers
+/// with a sparse `PATH` and no version managers. Not enabled by default, see
+/// `Finder::enable_providers` and `Finder::with_prefix_roots`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PrefixScanProvider {
+    roots: Vec<String>,
+}
+
+impl PrefixScanProvider {
+    pub fn new(roots: Vec<String>) -> Self {
+        Self { roots }
+    }
+}
+
+impl Provider for PrefixScanProvider {
+    fn create() -> Option<Self>
+    where
+        Self: Sized,
+    {
+        Some(Self::new(
+            DEFAULT_ROOTS.iter().map(|r| r.to_string()).collect(),
+        ))
+    }
+
+    fn find_pythons(&self) -> Vec<PythonVersion> {
+        self.roots
+            .iter()
+            .flat_map(|root| expand_root(root))
+            .flat_map(|dir| find_pythons_from_path(&dir, false))
+            .collect()
+    }
+}
+
+#[cfg(feature = "glob")]
+fn expand_root(root: &str) -> Vec<PathBuf> {
+    match glob::glob(root) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.is_dir())
+            .collect(),
+        Err(_) => vec![],
+    }
+}
+
+// Without the glob feature, only whole path components can be wildcards
+#[cfg(not(feature = "glob"))]
+fn expand_root(root: &str) -> Vec<PathBuf> {
+    let mut dirs = vec![PathBuf::new()];
+    for component in PathBuf::from(root).components() {
+        dirs = if component.as_os_str() == "*" {
+            dirs.iter()
+                .filter_map(|dir| dir.read_dir().ok())
+                .flatten()
+                .filter_map(|entry| Some(entry.ok()?.path()))
+                .filter(|path| path.is_dir())
+                .collect()
+        } else {
+            dirs.iter().map(|dir| dir.join(component)).collect()
+        };
+    }
+    dirs.into_iter().filter(|dir| dir.is_dir()).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::python::testing::TempDir;
+
+    #[test]
+    fn test_expand_root() {
+        let dir = TempDir::new("prefix");
+        std::fs::create_dir_all(dir.join("cp311/bin")).unwrap();
+        std::fs::create_dir_all(dir.join("cp312/bin")).unwrap();
+        std::fs::create_dir_all(dir.join("empty")).unwrap();
+
+        let mut expanded = expand_root(dir.join("*").join("bin").to_str().unwrap());
+        expanded.sort();
+        assert_eq!(expanded, vec![dir.join("cp311/bin"), dir.join("cp312/bin")]);
+        assert!(expand_root(dir.join("missing").to_str().unwrap()).is_empty());
+    }
+}
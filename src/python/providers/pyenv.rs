@@ -4,6 +4,7 @@ use shellexpand;
 use std::path::PathBuf;
 
 use super::Provider;
+use crate::python::python::PythonVersion;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(super) struct PyenvProvider {
@@ -14,19 +15,53 @@ impl PyenvProvider {
     pub fn new(root: PathBuf) -> Self {
         Self { root }
     }
+
+    /// The version pyenv runs outside of a project: the first one in `PYENV_VERSION`,
+    /// else in the global `version` file. `system` stands for the interpreter in `PATH`.
+    fn selected_version(&self) -> Option<String> {
+        let global = std::fs::read_to_string(self.root.join("version")).ok();
+        parse_selection(std::env::var("PYENV_VERSION").ok(), global)
+    }
+
+    // The first interpreter in PATH that isn't one of pyenv's shims
+    fn find_system_python(&self) -> Option<PythonVersion> {
+        let shims = self.root.join("shims");
+        let path_env = std::env::var_os("PATH")?;
+        std::env::split_paths(&path_env)
+            .filter(|path| *path != shims)
+            .flat_map(|path| super::find_pythons_from_path(&path, false))
+            .next()
+    }
+}
+
+fn parse_selection(env: Option<String>, global: Option<String>) -> Option<String> {
+    let from_env = env.and_then(|env| {
+        env.split(':')
+            .map(|v| v.trim().to_string())
+            .find(|v| !v.is_empty())
+    });
+    from_env.or_else(|| {
+        global?
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or_default())
+            .flat_map(|line| line.split_whitespace())
+            .map(|v| v.to_string())
+            .next()
+    })
 }
 
 impl Provider for PyenvProvider {
     fn create() -> Option<Self> {
         let pyenv_root = std::env::var_os("PYENV_ROOT").unwrap_or("$HOME/.pyenv".into());
+        let pyenv_root = pyenv_root.to_string_lossy();
 
         let root =
-            shellexpand::env_with_context_no_errors(pyenv_root.to_str().unwrap(), |var_name| {
+            shellexpand::env_with_context_no_errors(&pyenv_root, |var_name| {
                 let s = match var_name {
                     "HOME" => dirs::home_dir()?.into_os_string(),
                     var => std::env::var_os(var)?,
                 };
-                Some(s.into_string().unwrap())
+                s.into_string().ok()
             });
 
         let path = PathBuf::from(root.into_owned());
@@ -37,16 +72,21 @@ impl Provider for PyenvProvider {
         }
     }
 
-    fn find_pythons(&self) -> Vec<crate::python::python::PythonVersion> {
+    fn find_pythons(&self) -> Vec<PythonVersion> {
+        let selected = self.selected_version();
         let versions_path = self.root.join("versions");
-        match versions_path.read_dir() {
+        let mut pythons: Vec<PythonVersion> = match versions_path.read_dir() {
             Ok(entries) => entries
                 .into_iter()
                 .flat_map(|entry| match entry {
                     Ok(entry) => {
                         let path = entry.path();
                         if path.is_dir() {
+                            let is_default = selected.as_deref() == entry.file_name().to_str();
                             super::find_pythons_from_path(&path.join("bin"), true)
+                                .into_iter()
+                                .map(|python| python.with_is_default(is_default))
+                                .collect()
                         } else {
                             vec![]
                         }
@@ -55,6 +95,33 @@ impl Provider for PyenvProvider {
                 })
                 .collect(),
             Err(_) => vec![],
+        };
+        if selected.as_deref() == Some("system") {
+            if let Some(python) = self.find_system_python() {
+                pythons.push(python.with_is_default(true));
+            }
         }
+        pythons
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_selection() {
+        let some = |s: &str| Some(s.to_string());
+        assert_eq!(
+            parse_selection(some("3.11.4:3.12.1"), some("3.10.0")),
+            some("3.11.4")
+        );
+        assert_eq!(
+            parse_selection(some(""), some("# global\n3.10.0 3.9.7\n")),
+            some("3.10.0")
+        );
+        assert_eq!(parse_selection(None, some("system\n")), some("system"));
+        assert_eq!(parse_selection(None, some("\n")), None);
+        assert_eq!(parse_selection(None, None), None);
     }
 }
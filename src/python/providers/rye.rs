@@ -3,30 +3,22 @@
 use std::path::PathBuf;
 
 use super::Provider;
-use crate::python::python::PythonVersion;
+use crate::python::{helpers, python::PythonVersion};
 
+/// Searches the toolchains installed by rye, plus the interpreters linked from the
+/// rye and uv shim directories (`~/.rye/shims`, uv's `~/.local/bin`)
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(super) struct RyeProvider {
     root: PathBuf,
+    shim_dirs: Vec<(PathBuf, &'static str)>,
 }
 
 impl RyeProvider {
-    pub fn new(root: PathBuf) -> Self {
-        Self { root }
-    }
-}
-
-impl Provider for RyeProvider {
-    fn create() -> Option<Self>
-    where
-        Self: Sized,
-    {
-        let rye_root = std::env::var_os("RYE_ROOT")
-            .or_else(|| Some(dirs::home_dir()?.join(".rye").into_os_string()))?;
-        Some(Self::new(rye_root.into()))
+    pub fn new(root: PathBuf, shim_dirs: Vec<(PathBuf, &'static str)>) -> Self {
+        Self { root, shim_dirs }
     }
 
-    fn find_pythons(&self) -> Vec<PythonVersion> {
+    fn find_installed(&self) -> Vec<PythonVersion> {
         let py_root = self.root.join("py");
         match py_root.read_dir() {
             Ok(entries) => entries
@@ -46,4 +38,111 @@ impl Provider for RyeProvider {
             Err(_) => vec![],
         }
     }
+
+    // Shims that are symlinks to an interpreter, resolved to their target. Launchers
+    // that pick the interpreter at runtime (rye's own shim binary) aren't followed.
+    fn find_shims(&self, seen: &mut Vec<PathBuf>) -> Vec<PythonVersion> {
+        let mut found = vec![];
+        for (dir, tool) in &self.shim_dirs {
+            let entries = match dir.read_dir() {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+            let mut shims: Vec<PathBuf> = entries
+                .filter_map(|entry| Some(entry.ok()?.path()))
+                .filter(|path| path.is_symlink() && helpers::path_is_python(path))
+                .collect();
+            shims.sort();
+            for shim in shims {
+                let target = match shim.canonicalize() {
+                    Ok(target) => target,
+                    Err(_) => continue,
+                };
+                if seen.contains(&target) {
+                    continue;
+                }
+                seen.push(target.clone());
+                let mut python = PythonVersion::new(shim).with_interpreter(target);
+                python.formatted_name = Some(format!("{} shim", tool));
+                found.push(python);
+            }
+        }
+        found
+    }
+}
+
+impl Provider for RyeProvider {
+    fn create() -> Option<Self>
+    where
+        Self: Sized,
+    {
+        let rye_root = std::env::var_os("RYE_HOME")
+            .or_else(|| std::env::var_os("RYE_ROOT"))
+            .or_else(|| Some(dirs::home_dir()?.join(".rye").into_os_string()))?;
+        let rye_root = PathBuf::from(rye_root);
+
+        let mut shim_dirs = vec![(rye_root.join("shims"), "rye")];
+        let uv_bin = std::env::var_os("UV_PYTHON_BIN_DIR")
+            .map(PathBuf::from)
+            .or_else(|| Some(dirs::home_dir()?.join(".local").join("bin")));
+        if let Some(uv_bin) = uv_bin {
+            shim_dirs.push((uv_bin, "uv"));
+        }
+        Some(Self::new(rye_root, shim_dirs))
+    }
+
+    fn find_pythons(&self) -> Vec<PythonVersion> {
+        let mut pythons = self.find_installed();
+        // A shim pointing at one of the installs above would be a duplicate
+        let mut seen: Vec<PathBuf> = pythons.iter().map(|p| p.real_path()).collect();
+        pythons.extend(self.find_shims(&mut seen));
+        pythons
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_shims() {
+        use std::os::unix::fs::symlink;
+
+        let dir = std::env::temp_dir().join(format!("where-is-it-rye-{}", std::process::id()));
+        let installed = dir.join("rye/py/cpython@3.12.1/install/bin");
+        let uv_install = dir.join("uv/python/cpython-3.11.9/bin");
+        std::fs::create_dir_all(&installed).unwrap();
+        std::fs::create_dir_all(&uv_install).unwrap();
+        std::fs::create_dir_all(dir.join("rye/shims")).unwrap();
+        std::fs::create_dir_all(dir.join("bin")).unwrap();
+        std::fs::write(installed.join("python3"), "").unwrap();
+        std::fs::write(uv_install.join("python3.11"), "").unwrap();
+        // Points at the rye install, so it's already counted
+        symlink(installed.join("python3"), dir.join("rye/shims/python3")).unwrap();
+        // Both point at the same uv install
+        symlink(uv_install.join("python3.11"), dir.join("bin/python3.11")).unwrap();
+        symlink(dir.join("bin/python3.11"), dir.join("bin/python3")).unwrap();
+        // Not a link, so can't be resolved without running it
+        std::fs::write(dir.join("rye/shims/python"), "").unwrap();
+
+        let provider = RyeProvider::new(
+            dir.join("rye"),
+            vec![(dir.join("rye/shims"), "rye"), (dir.join("bin"), "uv")],
+        );
+        let found: Vec<(PathBuf, Option<String>)> = provider
+            .find_pythons()
+            .into_iter()
+            .map(|p| (p.executable, p.formatted_name))
+            .collect();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            found,
+            vec![
+                (installed.join("python3"), None),
+                (dir.join("bin/python3"), Some("uv shim".to_string())),
+            ]
+        );
+    }
 }
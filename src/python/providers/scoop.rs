@@ -0,0 +1,48 @@
+use std::path::PathBuf;
+
+use super::Provider;
+use crate::python::python::PythonVersion;
+
+/// A provider that searches the apps installed by Scoop, e.g. `scoop\apps\python\current`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ScoopProvider {
+    roots: Vec<PathBuf>,
+}
+
+impl ScoopProvider {
+    pub fn new(roots: Vec<PathBuf>) -> Self {
+        Self { roots }
+    }
+}
+
+impl Provider for ScoopProvider {
+    fn create() -> Option<Self>
+    where
+        Self: Sized,
+    {
+        Some(Self::new(crate::scoop::scoop_roots()))
+    }
+
+    fn find_pythons(&self) -> Vec<PythonVersion> {
+        self.roots
+            .iter()
+            .filter_map(|root| root.join("apps").read_dir().ok())
+            .flatten()
+            .filter_map(|app| {
+                let app = app.ok()?.path();
+                let current = app.join("current");
+                // `current` is a junction to the versioned directory, report that one instead
+                let home = match current.read_link() {
+                    Ok(target) => app.join(target),
+                    Err(_) => current,
+                };
+                let python = home.join("python.exe");
+                if python.is_file() {
+                    Some(PythonVersion::new(python))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
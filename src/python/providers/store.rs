@@ -0,0 +1,52 @@
+use std::path::PathBuf;
+
+use super::Provider;
+use crate::python::python::PythonVersion;
+
+/// A provider that searches the Python packages installed from the Microsoft Store,
+/// e.g. `WindowsApps\PythonSoftwareFoundation.Python.3.12_qbz5n2kfra8p0\python.exe`.
+/// The `python.exe` directly in `WindowsApps` is a stub opening the Store and is skipped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct StoreProvider {
+    root: PathBuf,
+}
+
+impl StoreProvider {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+}
+
+impl Provider for StoreProvider {
+    fn create() -> Option<Self>
+    where
+        Self: Sized,
+    {
+        let local_app_data = PathBuf::from(std::env::var_os("LOCALAPPDATA")?);
+        Some(Self::new(
+            local_app_data.join("Microsoft").join("WindowsApps"),
+        ))
+    }
+
+    fn find_pythons(&self) -> Vec<PythonVersion> {
+        let packages = match self.root.read_dir() {
+            Ok(packages) => packages,
+            Err(_) => return vec![],
+        };
+        packages
+            .filter_map(|package| {
+                let package = package.ok()?.path();
+                let name = package.file_name()?.to_str()?;
+                if !name.starts_with("PythonSoftwareFoundation.Python.") {
+                    return None;
+                }
+                // An app execution alias, which can't be followed by `is_file`
+                let python = package.join("python.exe");
+                python.symlink_metadata().ok()?;
+                let mut python = PythonVersion::new(python);
+                python.formatted_name = Some("Microsoft Store".to_string());
+                Some(python)
+            })
+            .collect()
+    }
+}
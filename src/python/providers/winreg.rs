@@ -1,7 +1,9 @@
 // Heavily adapted from https://github.com/frostming/findpython
 
 use pep440_rs::Version;
+use std::collections::HashSet;
 use std::io::Result;
+use std::path::PathBuf;
 use std::str::FromStr;
 use winreg::enums::*;
 use winreg::RegKey;
@@ -26,14 +28,17 @@ impl PythonRegSource {
         } else {
             None
         };
-        let install_path: String = reg
-            .open_subkey_with_flags("InstallPath", KEY_READ | self.flags)?
-            .get_value("ExecutablePath")?;
+        // Older and third-party installs only set the default value of InstallPath
+        let install_key = reg.open_subkey_with_flags("InstallPath", KEY_READ | self.flags)?;
+        let install_path = match install_key.get_value::<String, _>("ExecutablePath") {
+            Ok(path) => PathBuf::from(path),
+            Err(_) => PathBuf::from(install_key.get_value::<String, _>("")?).join("python.exe"),
+        };
         let arch = reg
             .get_value::<String, _>("SysArchitecture")
             .ok()
             .or_else(|| self.arch.map(|a| a.to_string()));
-        let mut py = PythonVersion::new(install_path.into());
+        let mut py = PythonVersion::new(install_path);
         if let Some(arch) = arch {
             py = py.with_architecture(arch.as_str());
         }
@@ -122,9 +127,12 @@ impl Provider for WinRegProvider {
     }
 
     fn find_pythons(&self) -> Vec<PythonVersion> {
+        // The same install can be registered under both HKCU and HKLM
+        let mut seen = HashSet::new();
         self.sources
             .iter()
             .flat_map(|s| s.find_all())
+            .filter(|py| seen.insert(py.executable.to_string_lossy().to_lowercase()))
             .collect::<Vec<_>>()
     }
 }
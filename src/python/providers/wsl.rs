@@ -0,0 +1,132 @@
+use std::{path::PathBuf, str::FromStr};
+
+use super::Provider;
+use crate::python::python::{python_command, PythonVersion};
+
+/// Prints the executable and version of the distro's default `python3`
+static PROBE_SCRIPT: &str =
+    "import sys, platform; print(sys.executable); print(platform.python_version())";
+
+/// A provider that finds the default `python3` of each WSL distro, by running it
+/// through `wsl.exe`. Their executables are reported as `\\wsl$\<distro>\...` paths,
+/// which can't be run from Windows, so the version is probed inside the distro.
+/// Not enabled by default as it starts every distro, see `Finder::enable_providers`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(not(windows), allow(dead_code))]
+pub(super) struct WslProvider {
+    distros: Vec<String>,
+}
+
+#[cfg_attr(not(windows), allow(dead_code))]
+impl WslProvider {
+    pub fn new(distros: Vec<String>) -> Self {
+        Self { distros }
+    }
+
+    fn find_python(&self, distro: &str) -> Option<PythonVersion> {
+        let output = python_command("wsl.exe")
+            .args(["-d", distro, "--", "python3", "-c", PROBE_SCRIPT])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let (executable, version) = parse_probe(&String::from_utf8_lossy(&output.stdout))?;
+        let executable = unc_path(distro, &executable);
+        let mut python = PythonVersion::new(executable.clone())
+            .with_interpreter(executable)
+            .with_version(version);
+        python.formatted_name = Some(format!("WSL '{}'", distro));
+        Some(python)
+    }
+}
+
+impl Provider for WslProvider {
+    fn create() -> Option<Self>
+    where
+        Self: Sized,
+    {
+        let output = python_command("wsl.exe")
+            .args(["--list", "--quiet"])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        Some(Self::new(parse_distros(&output.stdout)))
+    }
+
+    fn find_pythons(&self) -> Vec<PythonVersion> {
+        self.distros
+            .iter()
+            .filter_map(|distro| self.find_python(distro))
+            .collect()
+    }
+}
+
+// wsl.exe writes its own messages as UTF-16
+fn parse_distros(output: &[u8]) -> Vec<String> {
+    let is_utf16 =
+        output.len().is_multiple_of(2) && output.iter().skip(1).step_by(2).all(|b| *b == 0);
+    let text = if is_utf16 {
+        let units: Vec<u16> = output
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    } else {
+        String::from_utf8_lossy(output).into_owned()
+    };
+    text.lines()
+        .map(|line| line.trim_matches(|c: char| c.is_whitespace() || c == '\u{feff}'))
+        .filter(|line| !line.is_empty())
+        .map(|line| line.to_string())
+        .collect()
+}
+
+fn parse_probe(output: &str) -> Option<(String, pep440_rs::Version)> {
+    let mut lines = output.lines().map(|line| line.trim());
+    let executable = lines.next().filter(|e| e.starts_with('/'))?;
+    let version = lines.next()?.split('+').next()?;
+    Some((
+        executable.to_string(),
+        pep440_rs::Version::from_str(version).ok()?,
+    ))
+}
+
+// The path of a file of the distro as seen from Windows
+fn unc_path(distro: &str, path: &str) -> PathBuf {
+    let path = path.trim_start_matches('/').replace('/', "\\");
+    PathBuf::from(format!("\\\\wsl$\\{}\\{}", distro, path))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_distros() {
+        let utf16: Vec<u8> = "Ubuntu-22.04\r\ndocker-desktop\r\n\r\n"
+            .encode_utf16()
+            .flat_map(|u| u.to_le_bytes())
+            .collect();
+        assert_eq!(
+            parse_distros(&utf16),
+            vec!["Ubuntu-22.04", "docker-desktop"]
+        );
+        assert_eq!(parse_distros(b"Debian\n"), vec!["Debian"]);
+        assert!(parse_distros(b"").is_empty());
+    }
+
+    #[test]
+    fn test_parse_probe() {
+        let (executable, version) = parse_probe("/usr/bin/python3\n3.12.3\n").unwrap();
+        assert_eq!(executable, "/usr/bin/python3");
+        assert_eq!(version.to_string(), "3.12.3");
+        assert!(parse_probe("command not found\n").is_none());
+        assert_eq!(
+            unc_path("Ubuntu", "/usr/bin/python3"),
+            PathBuf::from("\\\\wsl$\\Ubuntu\\usr\\bin\\python3")
+        );
+    }
+}
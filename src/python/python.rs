@@ -1,16 +1,27 @@
 // Heavily adapted from https://github.com/frostming/findpython
 
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fmt;
+use std::marker::PhantomData;
+use std::process::Command;
+#[cfg(not(target_arch = "wasm32"))]
 use std::process::Stdio;
+#[cfg(not(target_arch = "wasm32"))]
 use std::time::Duration;
-use std::{hash::Hash, io, path::PathBuf, str::FromStr};
+use std::{
+    hash::Hash,
+    io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+#[cfg(not(target_arch = "wasm32"))]
 use wait_timeout::ChildExt;
 
 use pep440_rs::Version;
 
-use crate::python::finder::MatchOptions;
-use crate::python::helpers::calculate_file_hash;
+use crate::python::finder::{MatchOptions, PrereleaseKind};
+use crate::python::helpers::{calculate_file_hash, normalize_architecture};
 
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
@@ -18,32 +29,187 @@ use std::os::windows::process::CommandExt;
 #[cfg(target_os = "windows")]
 const CREATE_NO_WINDOW: u32 = 0x08000000;
 
+thread_local! {
+    // Versions probed while a `ProbeCacheScope` is alive, by executable identity
+    static PROBE_CACHE: RefCell<Option<HashMap<FileId, Version>>> = const { RefCell::new(None) };
+}
+
+/// Identifies the file behind a path, whichever symlink or bind mount it's reached through
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum FileId {
+    #[cfg(unix)]
+    Inode(u64, u64),
+    #[cfg(not(unix))]
+    Path(PathBuf),
+}
+
+#[cfg(unix)]
+fn file_id(path: &Path) -> Option<FileId> {
+    use std::os::unix::fs::MetadataExt;
+    let metadata = path.metadata().ok()?;
+    Some(FileId::Inode(metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(path: &Path) -> Option<FileId> {
+    Some(FileId::Path(path.canonicalize().ok()?))
+}
+
+/// While alive, interpreters sharing an executable (by inode, or canonical path on
+/// Windows) are only probed once for their version, on this thread. Nested scopes
+/// share the outermost one's cache, which is dropped with it. Not `Send`, as the
+/// cache belongs to the thread that entered the scope.
+pub(crate) struct ProbeCacheScope {
+    outermost: bool,
+    _thread: PhantomData<*const ()>,
+}
+
+impl ProbeCacheScope {
+    pub(crate) fn enter() -> Self {
+        PROBE_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            let outermost = cache.is_none();
+            if outermost {
+                *cache = Some(HashMap::new());
+            }
+            Self { outermost, _thread: PhantomData }
+        })
+    }
+}
+
+impl Drop for ProbeCacheScope {
+    fn drop(&mut self) {
+        if self.outermost {
+            PROBE_CACHE.with(|cache| cache.borrow_mut().take());
+        }
+    }
+}
+
 static GET_VERSION_TIMEOUT: u64 = 5;
+static PROBE_RETRIES: u32 = 2;
 
-fn run_python_script(cmd: &str, script: &str, timeout: Option<u64>) -> Result<String, io::Error> {
-    use std::process::Command;
-    let args = vec!["-EsSc", script];
+// Prints the PEP 508 environment markers, then the SOABI tag and site-packages
+// directory, as `name=value` lines
+static DETAILS_SCRIPT: &str = r#"
+import os, sys, platform, site, sysconfig
+def fmt(info):
+    v = '{0.major}.{0.minor}.{0.micro}'.format(info)
+    if info.releaselevel != 'final':
+        v += info.releaselevel[0] + str(info.serial)
+    return v
+markers = {
+    'implementation_name': sys.implementation.name,
+    'implementation_version': fmt(sys.implementation.version),
+    'os_name': os.name,
+    'platform_machine': platform.machine(),
+    'platform_python_implementation': platform.python_implementation(),
+    'platform_release': platform.release(),
+    'platform_system': platform.system(),
+    'platform_version': platform.version(),
+    'python_full_version': platform.python_version(),
+    'python_version': '.'.join(platform.python_version_tuple()[:2]),
+    'sys_platform': sys.platform,
+}
+for name, value in markers.items():
+    print(name + '=' + value)
+print('soabi=' + (sysconfig.get_config_var('SOABI') or ''))
+# getsitepackages is empty (or missing) in some virtual environments
+paths = getattr(site, 'getsitepackages', lambda: [])()
+print('site_packages=' + (paths[0] if paths else sysconfig.get_path('purelib')))
+"#;
+
+fn run_python_script(
+    cmd: &str,
+    script: &str,
+    timeout: Option<u64>,
+    retries: u32,
+) -> Result<Vec<u8>, io::Error> {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("run_python_script", cmd).entered();
+    #[cfg(feature = "tracing")]
+    let start = std::time::Instant::now();
+
+    let result = retry_transient(retries, || _run_python_script(cmd, script, timeout));
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!(success = result.is_ok(), elapsed = ?start.elapsed(), "python script finished");
+
+    result
+}
+
+/// Whether an error is a temporary failure to spawn a process, e.g. `EAGAIN` on a
+/// busy machine. Exit codes, timeouts and unparsable output are never transient.
+fn is_transient_spawn_error(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted
+    )
+}
+
+/// Run `f` again up to `retries` times while it fails with a transient spawn error,
+/// waiting a little longer before each attempt
+fn retry_transient<T>(
+    retries: u32,
+    mut f: impl FnMut() -> Result<T, io::Error>,
+) -> Result<T, io::Error> {
+    let mut attempt = 0;
+    loop {
+        match f() {
+            Err(err) if attempt < retries && is_transient_spawn_error(&err) => {
+                attempt += 1;
+                std::thread::sleep(std::time::Duration::from_millis(50 * attempt as u64));
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Creates a command for the given interpreter that won't open a console window on Windows
+pub(crate) fn python_command(cmd: &str) -> Command {
+    #[allow(unused_mut)]
     let mut command = Command::new(cmd);
-    command.args(args)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::null());
 
     #[cfg(target_os = "windows")]
     command.creation_flags(CREATE_NO_WINDOW);
 
+    command
+}
+
+// Strict, as probes may print paths that a lossy decoding would silently corrupt
+fn decode_output(cmd: &str, stdout: Vec<u8>) -> Result<String, io::Error> {
+    String::from_utf8(stdout)
+        .map_err(|e| io::Error::other(format!("Command '{}' output is not valid UTF-8: {}", cmd, e)))
+}
+
+/// The version printed by the version probe, taken from the last line that is
+/// one so that any noise printed before it is skipped
+fn parse_version_output(output: &str) -> Result<Version, io::Error> {
+    output
+        .lines()
+        .rev()
+        .find_map(crate::python::normalize_version)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                format!("Failed to parse Python version '{}'", output.trim()),
+            )
+        })
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn _run_python_script(cmd: &str, script: &str, timeout: Option<u64>) -> Result<Vec<u8>, io::Error> {
+    let args = vec!["-EsSc", script];
+    let mut command = python_command(cmd);
+    command.args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+
     let mut child = command.spawn()?;
     match timeout {
         Some(duration) => match child.wait_timeout(Duration::from_secs(duration as u64))? {
             Some(status) => {
                 if status.success() {
-                    Ok(
-                        String::from_utf8(child.wait_with_output()?.stdout).map_err(|e| {
-                            io::Error::new(
-                                io::ErrorKind::Other,
-                                format!("Command '{}' output is not valid UTF-8: {}", cmd, e),
-                            )
-                        })?,
-                    )
+                    Ok(child.wait_with_output()?.stdout)
                 } else {
                     Err(io::Error::new(
                         io::ErrorKind::Other,
@@ -76,26 +242,50 @@ fn run_python_script(cmd: &str, script: &str, timeout: Option<u64>) -> Result<St
                     ),
                 ));
             }
-            Ok(String::from_utf8(output.stdout).map_err(|e| {
-                io::Error::new(
-                    io::ErrorKind::Other,
-                    format!("Command '{}' output is not valid UTF-8: {}", cmd, e),
-                )
-            })?)
+            Ok(output.stdout)
         }
     }
 }
 
+// Interpreters can't be spawned from wasm, so every probe fails
+#[cfg(target_arch = "wasm32")]
+fn _run_python_script(cmd: &str, _script: &str, _timeout: Option<u64>) -> Result<Vec<u8>, io::Error> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("Command '{}' can't be run on wasm", cmd),
+    ))
+}
+
 #[derive(Debug, Clone)]
 pub struct PythonVersion {
     /// The path to the Python executable.
     pub executable: PathBuf,
     pub formatted_name: Option<String>,
+    /// The name of the provider that found the interpreter.
+    pub provider: Option<String>,
     version: RefCell<Option<Version>>,
     interpreter: RefCell<Option<PathBuf>>,
     architecture: RefCell<Option<String>>,
+    machine: RefCell<Option<String>>,
+    markers: RefCell<Option<HashMap<String, String>>>,
+    soabi: RefCell<Option<String>>,
+    site_packages: RefCell<Option<PathBuf>>,
+    has_pip: RefCell<Option<bool>>,
+    has_tkinter: RefCell<Option<bool>>,
+    has_ssl: RefCell<Option<bool>>,
     /// Whether to keep the symlink to the Python executable.
     pub keep_symlink: bool,
+    /// Whether this is the version selected by pyenv (`PYENV_VERSION` or its global
+    /// `version` file), i.e. what `pyenv which python` runs.
+    pub is_default: bool,
+    /// The position in `PATH` of the directory holding the interpreter, for those
+    /// found by the `path` provider. Lower is what a shell would run first.
+    pub path_index: Option<usize>,
+    /// Timeout in seconds for each probe of the interpreter.
+    pub timeout: u64,
+    /// How many times a probe is retried when the interpreter fails to spawn
+    /// temporarily (e.g. `EAGAIN` on a busy machine).
+    pub retries: u32,
 }
 
 impl PythonVersion {
@@ -103,10 +293,22 @@ impl PythonVersion {
         Self {
             executable,
             formatted_name: None,
+            provider: None,
             version: RefCell::new(None),
             interpreter: RefCell::new(None),
             architecture: RefCell::new(None),
+            machine: RefCell::new(None),
+            markers: RefCell::new(None),
+            soabi: RefCell::new(None),
+            site_packages: RefCell::new(None),
+            has_pip: RefCell::new(None),
+            has_tkinter: RefCell::new(None),
+            has_ssl: RefCell::new(None),
             keep_symlink: false,
+            is_default: false,
+            path_index: None,
+            timeout: GET_VERSION_TIMEOUT,
+            retries: PROBE_RETRIES,
         }
     }
 
@@ -130,6 +332,46 @@ impl PythonVersion {
         self
     }
 
+    pub fn with_is_default(mut self, is_default: bool) -> Self {
+        self.is_default = is_default;
+        self
+    }
+
+    pub fn with_path_index(mut self, path_index: usize) -> Self {
+        self.path_index = Some(path_index);
+        self
+    }
+
+    pub fn with_provider(mut self, provider: &str) -> Self {
+        self.provider = Some(provider.to_string());
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: u64) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Forget every probed value so the next access runs the interpreter again,
+    /// e.g. after it was reinstalled. Values given through `with_*` are dropped too.
+    pub fn refresh(&self) {
+        self.version.borrow_mut().take();
+        self.interpreter.borrow_mut().take();
+        self.architecture.borrow_mut().take();
+        self.machine.borrow_mut().take();
+        self.markers.borrow_mut().take();
+        self.soabi.borrow_mut().take();
+        self.site_packages.borrow_mut().take();
+        self.has_pip.borrow_mut().take();
+        self.has_tkinter.borrow_mut().take();
+        self.has_ssl.borrow_mut().take();
+    }
+
     pub fn real_path(&self) -> PathBuf {
         self.executable
             .canonicalize()
@@ -140,39 +382,97 @@ impl PythonVersion {
         self.version().is_ok()
     }
 
+    /// Run an arbitrary script with the interpreter and return its stdout.
+    ///
+    /// The interpreter is invoked as `<executable> -EsSc <script>`, i.e. in isolated
+    /// mode: `PYTHON*` environment variables are ignored and neither the user
+    /// site-packages nor the `site` module are loaded.
+    pub fn run_script(&self, script: &str, timeout: Option<u64>) -> Result<String, io::Error> {
+        let cmd = self.executable.to_string_lossy();
+        decode_output(&cmd, run_python_script(&cmd, script, timeout, self.retries)?)
+    }
+
     fn _get_version(&self) -> Result<Version, io::Error> {
         let script = "import platform; print(platform.python_version())";
-        let output = run_python_script(
-            &self.executable.to_string_lossy(),
-            script,
-            Some(GET_VERSION_TIMEOUT),
-        )?;
-        let version = output.trim().split('+').next().unwrap();
-        Version::from_str(version).map_err(|e| {
-            io::Error::new(
-                io::ErrorKind::Other,
-                format!("Failed to parse Python version '{}': {}", version, e),
-            )
-        })
+        let cmd = self.executable.to_string_lossy();
+        let output = run_python_script(&cmd, script, Some(self.timeout), self.retries)?;
+        // Some locales leak non-UTF-8 text (e.g. a sitecustomize banner) into the
+        // output, which shouldn't fail the version probe as the version is ASCII
+        parse_version_output(&String::from_utf8_lossy(&output))
     }
 
     fn _get_interpreter(&self) -> Result<PathBuf, io::Error> {
         let script = "import sys; print(sys.executable)";
-        let output = run_python_script(&self.executable.to_string_lossy(), script, None)?;
+        let output = self.run_script(script, Some(self.timeout))?;
         Ok(PathBuf::from(output.trim()))
     }
 
-    fn _get_architecture(&self) -> Result<String, io::Error> {
-        let script = "import platform; print(platform.architecture()[0])";
-        run_python_script(&self.executable.to_string_lossy(), script, None)
-            .map(|v| v.trim().to_string())
+    fn _get_architecture(&self) -> Result<(String, String), io::Error> {
+        let script = "import platform; print(platform.architecture()[0]); print(platform.machine())";
+        let output = self.run_script(script, Some(self.timeout))?;
+        let mut lines = output.lines().map(|l| l.trim().to_string());
+        Ok((
+            lines.next().unwrap_or_default(),
+            lines.next().unwrap_or_default(),
+        ))
+    }
+
+    // The markers, SOABI tag and site-packages directory, probed together as
+    // they're reported together
+    fn _get_details(&self) -> Result<(HashMap<String, String>, String, PathBuf), io::Error> {
+        let output = self.run_script(DETAILS_SCRIPT, Some(self.timeout))?;
+        let mut markers: HashMap<String, String> = output
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .map(|(name, value)| (name.to_string(), value.trim_end().to_string()))
+            .collect();
+        let soabi = markers.remove("soabi").unwrap_or_default();
+        let site_packages = markers.remove("site_packages").ok_or_else(|| {
+            io::Error::other(format!(
+                "Failed to parse the details of '{}'",
+                self.executable.display()
+            ))
+        })?;
+        Ok((markers, soabi, PathBuf::from(site_packages)))
+    }
+
+    fn _get_has_module(&self, module: &str) -> Result<bool, io::Error> {
+        let script = format!(
+            "import importlib.util; print(importlib.util.find_spec('{}') is not None)",
+            module
+        );
+        let output = self.run_script(&script, Some(self.timeout))?;
+        Ok(output.trim() == "True")
+    }
+
+    // Probe the version, or reuse that of the same executable within a `ProbeCacheScope`
+    fn _get_shared_version(&self) -> Result<Version, io::Error> {
+        let id = PROBE_CACHE.with(|cache| match cache.borrow().as_ref() {
+            Some(_) => file_id(&self.executable),
+            None => None,
+        });
+        let id = match id {
+            Some(id) => id,
+            None => return self._get_version(),
+        };
+        let cached = PROBE_CACHE.with(|cache| cache.borrow().as_ref()?.get(&id).cloned());
+        if let Some(version) = cached {
+            return Ok(version);
+        }
+        let version = self._get_version()?;
+        PROBE_CACHE.with(|cache| {
+            if let Some(cache) = cache.borrow_mut().as_mut() {
+                cache.insert(id, version.clone());
+            }
+        });
+        Ok(version)
     }
 
     pub fn version(&self) -> Result<Version, io::Error> {
         let mut inner = self.version.borrow_mut();
         match inner.as_ref() {
             Some(version) => Ok(version.clone()),
-            None => Ok(inner.insert(self._get_version()?).clone()),
+            None => Ok(inner.insert(self._get_shared_version()?).clone()),
         }
     }
 
@@ -184,29 +484,281 @@ impl PythonVersion {
         }
     }
 
+    /// The bitness of the interpreter, e.g. `64bit` or `32bit`.
     pub fn architecture(&self) -> Result<String, io::Error> {
         let mut inner = self.architecture.borrow_mut();
         match inner.as_ref() {
             Some(architecture) => Ok(architecture.clone()),
-            None => Ok(inner.insert(self._get_architecture()?).clone()),
+            None => {
+                let (architecture, machine) = self._get_architecture()?;
+                self.machine.borrow_mut().get_or_insert(machine);
+                Ok(inner.insert(architecture).clone())
+            }
         }
     }
 
+    /// The machine type reported by `platform.machine()`, e.g. `x86_64` or `arm64`.
+    pub fn machine(&self) -> Result<String, io::Error> {
+        let mut inner = self.machine.borrow_mut();
+        match inner.as_ref() {
+            Some(machine) => Ok(machine.clone()),
+            None => {
+                let (architecture, machine) = self._get_architecture()?;
+                self.architecture.borrow_mut().get_or_insert(architecture);
+                Ok(inner.insert(machine).clone())
+            }
+        }
+    }
+
+    /// The bitness (`64bit`, `32bit`) if already known, e.g. from the registry or an
+    /// earlier probe. Never runs the interpreter.
+    pub(crate) fn known_architecture(&self) -> Option<String> {
+        self.architecture.borrow().clone()
+    }
+
+    /// Check the interpreter against an architecture filter. Bitness filters
+    /// (`64bit`, `32bit`) compare against `architecture()`, anything else is
+    /// normalized and compared against the machine type, taking 32-bit builds
+    /// on 64-bit machines into account.
+    pub fn matches_architecture(&self, arch: &str) -> bool {
+        let arch = normalize_architecture(arch);
+        let bits = match self.architecture() {
+            Ok(bits) => bits,
+            Err(_) => return false,
+        };
+        if arch.ends_with("bit") {
+            return bits == arch;
+        }
+        let machine = match self.machine() {
+            Ok(machine) => normalize_architecture(&machine),
+            Err(_) => return false,
+        };
+        let machine = match (machine.as_str(), bits.as_str()) {
+            ("x86_64", "32bit") => "x86".to_string(),
+            ("aarch64", "32bit") => "arm".to_string(),
+            _ => machine,
+        };
+        machine == arch
+    }
+
+    /// The PEP 508 environment markers (`sys_platform`, `platform_machine`,
+    /// `python_full_version`, ...) of the interpreter, probed in a single run
+    /// along with `soabi` and `site_packages`.
+    pub fn markers(&self) -> Result<HashMap<String, String>, io::Error> {
+        let mut inner = self.markers.borrow_mut();
+        match inner.as_ref() {
+            Some(markers) => Ok(markers.clone()),
+            None => {
+                let (markers, soabi, site_packages) = self._get_details()?;
+                self.soabi.borrow_mut().get_or_insert(soabi);
+                self.site_packages.borrow_mut().get_or_insert(site_packages);
+                Ok(inner.insert(markers).clone())
+            }
+        }
+    }
+
+    /// Whether the interpreter belongs to a virtual environment, i.e. a
+    /// `pyvenv.cfg` exists one or two directories above the executable.
+    pub fn is_venv(&self) -> bool {
+        self.executable
+            .ancestors()
+            .skip(1)
+            .take(2)
+            .any(|dir| dir.join("pyvenv.cfg").is_file())
+    }
+
+    /// The ABI tag of the interpreter, e.g. `cpython-311-x86_64-linux-gnu`.
+    /// Empty for interpreters that don't define `SOABI` (some Windows builds).
+    /// Probed along with `markers`.
+    pub fn soabi(&self) -> Result<String, io::Error> {
+        let mut inner = self.soabi.borrow_mut();
+        match inner.as_ref() {
+            Some(soabi) => Ok(soabi.clone()),
+            None => {
+                let (markers, soabi, site_packages) = self._get_details()?;
+                self.markers.borrow_mut().get_or_insert(markers);
+                self.site_packages.borrow_mut().get_or_insert(site_packages);
+                Ok(inner.insert(soabi).clone())
+            }
+        }
+    }
+
+    /// The `site-packages` directory of the interpreter, where packages are installed.
+    /// Probed along with `markers`.
+    pub fn site_packages(&self) -> Result<PathBuf, io::Error> {
+        let mut inner = self.site_packages.borrow_mut();
+        match inner.as_ref() {
+            Some(site_packages) => Ok(site_packages.clone()),
+            None => {
+                let (markers, soabi, site_packages) = self._get_details()?;
+                self.markers.borrow_mut().get_or_insert(markers);
+                self.soabi.borrow_mut().get_or_insert(soabi);
+                Ok(inner.insert(site_packages).clone())
+            }
+        }
+    }
+
+    /// Whether the files needed to build C extensions against this interpreter are
+    /// installed, as in distributions' `python3-dev` packages: a `python3.X-config`
+    /// (or `python3-config`, `python-config`) script next to the interpreter, and
+    /// `Python.h` in the `include` directory of its prefix. Windows installs have no
+    /// config script, so only the headers are checked there. Doesn't run the interpreter.
+    pub fn has_dev_tools(&self) -> bool {
+        let real_path = self.real_path();
+        let bin = match real_path.parent() {
+            Some(bin) => bin,
+            None => return false,
+        };
+        if cfg!(windows) {
+            return bin.join("include").join("Python.h").is_file();
+        }
+
+        // The config script matching a versioned executable, else any of them
+        let name = real_path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        let has_config = match name.strip_prefix("python3.") {
+            Some(minor) if minor.chars().all(|c| c.is_ascii_digit()) => {
+                bin.join(format!("{}-config", name)).is_file()
+            }
+            _ => ["python3-config", "python-config"]
+                .iter()
+                .any(|config| bin.join(config).is_file())
+                || bin.read_dir().is_ok_and(|entries| {
+                    entries.filter_map(|e| e.ok()).any(|e| {
+                        let name = e.file_name();
+                        let name = name.to_string_lossy();
+                        name.starts_with("python3.") && name.ends_with("-config")
+                    })
+                }),
+        };
+        let include = match bin.parent() {
+            Some(prefix) => prefix.join("include"),
+            None => return false,
+        };
+        // e.g. include/python3.12/Python.h, or include/python3.13t for free-threaded builds
+        let has_headers = include.read_dir().is_ok_and(|entries| {
+            entries.filter_map(|e| e.ok()).any(|e| {
+                e.file_name().to_string_lossy().starts_with("python")
+                    && e.path().join("Python.h").is_file()
+            })
+        });
+        has_config && has_headers
+    }
+
+    /// Whether pip can be imported, which isn't the case for Windows embeddable
+    /// distributions and some minimal standalone builds. `false` if the probe fails.
+    pub fn has_pip(&self) -> bool {
+        let mut inner = self.has_pip.borrow_mut();
+        match inner.as_ref() {
+            Some(has_pip) => *has_pip,
+            None => *inner.insert(self._get_has_module("pip").unwrap_or(false)),
+        }
+    }
+
+    /// Whether tkinter can be imported, which many Linux system interpreters lack
+    /// until their `tk` package is installed. `false` if the probe fails.
+    pub fn has_tkinter(&self) -> bool {
+        let mut inner = self.has_tkinter.borrow_mut();
+        match inner.as_ref() {
+            Some(has_tkinter) => *has_tkinter,
+            None => *inner.insert(self._get_has_module("tkinter").unwrap_or(false)),
+        }
+    }
+
+    /// Whether the `ssl` module is usable, which some minimal and standalone builds
+    /// lack, breaking pip over HTTPS. Looks for its `_ssl` extension, as `ssl.py`
+    /// itself is always there. `false` if the probe fails.
+    pub fn has_ssl(&self) -> bool {
+        let mut inner = self.has_ssl.borrow_mut();
+        match inner.as_ref() {
+            Some(has_ssl) => *has_ssl,
+            None => *inner.insert(self._get_has_module("_ssl").unwrap_or(false)),
+        }
+    }
+
+    /// The C library (`glibc` or `musl`) of a standalone build, inferred from the
+    /// naming of its install directory - e.g. `cpython-3.12.1-linux-x86_64-musl`.
+    /// `None` when the path doesn't tell, as for most system interpreters.
+    pub fn libc(&self) -> Option<&'static str> {
+        self.executable.ancestors().find_map(|dir| {
+            let name = dir.file_name()?.to_str()?;
+            if name.ends_with("-musl") || name.contains("-musl-") {
+                Some("musl")
+            } else if name.ends_with("-gnu") || name.contains("-gnu-") {
+                Some("glibc")
+            } else {
+                None
+            }
+        })
+    }
+
     pub fn content_hash(&self) -> Result<String, io::Error> {
         calculate_file_hash(&PathBuf::from(&self.executable))
     }
 
-    pub fn matches(&self, options: &MatchOptions) -> bool {
+    /// Whether the executable's file name is `name` (or starts with it, if
+    /// `is_prefix`), ignoring an `.exe` suffix on either.
+    pub fn matches_name(&self, name: &str, is_prefix: bool) -> bool {
+        let strip_exe = |name: &str| -> String {
+            if name.to_ascii_lowercase().ends_with(".exe") {
+                name[..name.len() - 4].to_string()
+            } else {
+                name.to_string()
+            }
+        };
+        let file_name = match self.executable.file_name().and_then(|f| f.to_str()) {
+            Some(file_name) => strip_exe(file_name),
+            None => return false,
+        };
+        let name = strip_exe(name);
+        if is_prefix {
+            file_name.starts_with(&name)
+        } else {
+            file_name == name
+        }
+    }
+
+    /// Check the filters that don't depend on the interpreter's version.
+    pub fn matches_unversioned(&self, options: &MatchOptions) -> bool {
         if let Some(name) = options.name.as_ref() {
-            if self.executable.file_name().unwrap().to_str() != Some(name.as_str()) {
+            if !self.matches_name(name, options.name_is_prefix.unwrap_or(false)) {
+                return false;
+            }
+        }
+        if let Some(venv) = options.venv {
+            if self.is_venv() != venv {
+                return false;
+            }
+        }
+        if !options.exclude_paths.is_empty() {
+            let real_path = self.real_path();
+            let excluded = options.exclude_paths.iter().any(|prefix| {
+                let prefix = PathBuf::from(prefix);
+                real_path.starts_with(prefix.canonicalize().unwrap_or(prefix))
+            });
+            if excluded {
                 return false;
             }
         }
         if let Some(arch) = options.architecture.as_ref() {
-            if self.architecture().is_err() || self.architecture().unwrap().as_str() != arch {
+            if !self.matches_architecture(arch) {
+                return false;
+            }
+        }
+        if let (Some(libc), Some(own_libc)) = (options.libc.as_ref(), self.libc()) {
+            if !libc.eq_ignore_ascii_case(own_libc) {
                 return false;
             }
         }
+        if options.require_dev == Some(true) && !self.has_dev_tools() {
+            return false;
+        }
+        true
+    }
+
+    pub fn matches(&self, options: &MatchOptions) -> bool {
+        if !self.matches_unversioned(options) {
+            return false;
+        }
 
         if let Ok(version) = self.version() {
             if let Some(major) = options.major {
@@ -234,6 +786,34 @@ impl PythonVersion {
                     return false;
                 }
             }
+            // An unparsable bound matches nothing rather than being ignored
+            if let Some(min_version) = &options.min_version {
+                match Version::from_str(min_version) {
+                    Ok(min_version) if version >= min_version => {}
+                    _ => return false,
+                }
+            }
+            if let Some(max_version) = &options.max_version {
+                match Version::from_str(max_version) {
+                    Ok(max_version) if version <= max_version => {}
+                    _ => return false,
+                }
+            }
+            if let Some(kind) = options.prerelease {
+                if version.pre.map(|(pre, _)| PrereleaseKind::from(pre)) != Some(kind) {
+                    return false;
+                }
+            }
+            // Probed last, as they run the interpreter once more
+            if options.require_pip == Some(true) && !self.has_pip() {
+                return false;
+            }
+            if options.require_tkinter == Some(true) && !self.has_tkinter() {
+                return false;
+            }
+            if options.require_ssl == Some(true) && !self.has_ssl() {
+                return false;
+            }
             true
         } else {
             false
@@ -267,3 +847,96 @@ impl PartialEq for PythonVersion {
 }
 
 impl Eq for PythonVersion {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[cfg(unix)]
+    use crate::python::testing::{fake_script, TempDir};
+
+    #[cfg(unix)]
+    #[test]
+    fn test_probe_cache_scope() {
+        let dir = TempDir::new("probe-cache");
+        let counter = dir.join("probes");
+        let script = format!("echo x >> {}\necho 3.12.1", counter.display());
+        let python = fake_script(&dir, "python3", &script);
+        std::os::unix::fs::symlink(&python, dir.join("python")).unwrap();
+        let probes = || std::fs::read_to_string(&counter).unwrap_or_default().lines().count();
+
+        {
+            let _scope = ProbeCacheScope::enter();
+            let _nested = ProbeCacheScope::enter();
+            assert!(PythonVersion::new(python.clone()).is_valid());
+            assert!(PythonVersion::new(dir.join("python")).is_valid());
+        }
+        let shared = probes();
+        assert!(PythonVersion::new(dir.join("python")).is_valid());
+        let unscoped = probes();
+
+        assert_eq!(shared, 1);
+        assert_eq!(unscoped, 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_invalid_utf8_output() {
+        let dir = TempDir::new("utf8");
+        let python = fake_script(&dir, "python3", "printf 'Bienvenue \\351\\377\\n3.12.1\\n'");
+
+        let python = PythonVersion::new(python);
+        let version = python.version().map(|v| v.to_string());
+        // Path-returning probes are decoded strictly rather than corrupting the path
+        let interpreter = python.interpreter();
+
+        assert_eq!(version.unwrap(), "3.12.1");
+        assert!(interpreter.is_err());
+        assert!(parse_version_output("banner\n").is_err());
+    }
+
+    #[test]
+    fn test_libc() {
+        let python = |path: &str| PythonVersion::new(PathBuf::from(path));
+        assert_eq!(
+            python("/home/me/.local/share/uv/python/cpython-3.12.1-linux-x86_64-musl/bin/python3").libc(),
+            Some("musl")
+        );
+        assert_eq!(
+            python("/opt/cpython-3.11.7+20240107-x86_64-unknown-linux-gnu/install/bin/python3").libc(),
+            Some("glibc")
+        );
+        assert_eq!(python("/usr/bin/python3").libc(), None);
+    }
+
+    #[test]
+    fn test_retry_transient_spawn_error() {
+        let mut calls = 0;
+        let result = retry_transient(2, || {
+            calls += 1;
+            if calls == 1 {
+                Err(io::Error::from(io::ErrorKind::WouldBlock))
+            } else {
+                Ok("3.12.1")
+            }
+        });
+        assert_eq!(result.unwrap(), "3.12.1");
+        assert_eq!(calls, 2);
+
+        // Exit codes and the like fail right away, and the retries are bounded
+        let mut calls = 0;
+        let result: Result<(), _> = retry_transient(2, || {
+            calls += 1;
+            Err(io::Error::other("exit code 1"))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+
+        let mut calls = 0;
+        let result: Result<(), _> = retry_transient(2, || {
+            calls += 1;
+            Err(io::Error::from(io::ErrorKind::WouldBlock))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 3);
+    }
+}
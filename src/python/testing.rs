@@ -0,0 +1,49 @@
+//! Fixtures shared by the Python tests
+
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+/// A directory under the system temp dir, `where-is-it-<name>-<pid>`, removed
+/// again when dropped so a failing test doesn't leave it behind
+pub(crate) struct TempDir(PathBuf);
+
+impl TempDir {
+    pub(crate) fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("where-is-it-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Writes an executable shell script at `dir/name`, creating its parents
+#[cfg(unix)]
+pub(crate) fn fake_script(dir: &Path, name: &str, body: &str) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let script = dir.join(name);
+    std::fs::create_dir_all(script.parent().unwrap()).unwrap();
+    std::fs::write(&script, format!("#!/bin/sh\n{}\n", body)).unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+    script
+}
+
+/// A fake interpreter at `dir/name` answering every probe with `version_output`
+#[cfg(unix)]
+pub(crate) fn fake_python(dir: &Path, name: &str, version_output: &str) -> PathBuf {
+    fake_script(dir, name, &format!("echo {}", version_output))
+}
@@ -0,0 +1,13 @@
+use std::path::PathBuf;
+
+/// The Scoop install roots: the user one (`SCOOP`, by default `~\scoop`), then the
+/// global one (`SCOOP_GLOBAL`, by default `%ProgramData%\scoop`)
+pub(crate) fn scoop_roots() -> Vec<PathBuf> {
+    let user_root = std::env::var_os("SCOOP")
+        .map(PathBuf::from)
+        .or_else(|| Some(dirs::home_dir()?.join("scoop")));
+    let global_root = std::env::var_os("SCOOP_GLOBAL")
+        .map(PathBuf::from)
+        .or_else(|| Some(PathBuf::from(std::env::var_os("ProgramData")?).join("scoop")));
+    [user_root, global_root].into_iter().flatten().collect()
+}
@@ -0,0 +1,45 @@
+use serde::Serialize;
+
+/// An inventory of the toolchains found on this machine, as returned by `discover_all`
+#[derive(Debug, Clone, Serialize)]
+pub struct DiscoverySummary {
+    /// The operating system, e.g. `linux`, `macos` or `windows`
+    pub host_os: String,
+    /// The CPU architecture, e.g. `x86_64` or `aarch64`
+    pub host_arch: String,
+    #[cfg(feature = "java")]
+    pub jvms: Vec<crate::java::Jvm>,
+    #[cfg(feature = "python")]
+    pub pythons: Vec<crate::python::Version>,
+    /// Why the discovery of a language failed, prefixed with the language
+    pub errors: Vec<String>,
+}
+
+/// Find every JVM and Python interpreter with the default options. A language
+/// whose discovery fails contributes an entry to `errors` instead of its results.
+pub fn discover_all() -> DiscoverySummary {
+    #[allow(unused_mut)]
+    let mut errors = vec![];
+
+    #[cfg(feature = "java")]
+    let jvms = match crate::java::try_run(Default::default()) {
+        Ok(jvms) => jvms,
+        Err(err) => {
+            errors.push(format!("java: {}", err));
+            vec![]
+        }
+    };
+
+    #[cfg(feature = "python")]
+    let pythons = crate::python::run(Default::default());
+
+    DiscoverySummary {
+        host_os: std::env::consts::OS.to_string(),
+        host_arch: std::env::consts::ARCH.to_string(),
+        #[cfg(feature = "java")]
+        jvms,
+        #[cfg(feature = "python")]
+        pythons,
+        errors,
+    }
+}